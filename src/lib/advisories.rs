@@ -0,0 +1,536 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+use prettytable::{color, Attr, Row};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::lockfile::Lockfile;
+use crate::node_module::standard_module::StandardModule;
+use crate::node_module::{new_cell, PrintTable};
+use crate::range::Range;
+use crate::registry_audit::get_name_from_lock_path;
+use crate::semver::Semver;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Unknown,
+    Low,
+    Moderate,
+    High,
+    Critical,
+}
+
+impl Severity {
+    fn from(severity: &str) -> Self {
+        match severity.to_lowercase().as_str() {
+            "info" | "low" => Severity::Low,
+            "moderate" | "medium" => Severity::Moderate,
+            "high" => Severity::High,
+            "critical" => Severity::Critical,
+            _ => Severity::Unknown,
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Severity::Unknown => "unknown",
+            Severity::Low => "low",
+            Severity::Moderate => "moderate",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        }
+    }
+}
+
+/// A security advisory for one package.
+#[derive(Debug)]
+pub struct Advisory {
+    pub id: String,
+    pub summary: String,
+    pub severity: Severity,
+    affected: Affected,
+}
+
+#[derive(Debug)]
+enum Affected {
+    /// The `ranges` and `versions` of an OSV advisory.
+    Intervals {
+        intervals: Vec<Interval>,
+        versions: Vec<String>,
+    },
+    /// The `vulnerable_versions` and `patched_versions` of an npm advisory.
+    Range {
+        range: Range,
+        patched: Option<Range>,
+    },
+}
+
+/// The versions from `introduced` up to `fixed` or `last_affected`, from the events of an OSV
+/// range. No `introduced` version means every version before the end is affected.
+#[derive(Debug)]
+struct Interval {
+    introduced: Option<Semver>,
+    fixed: Option<Semver>,
+    last_affected: Option<Semver>,
+}
+
+impl Interval {
+    fn contains(&self, version: &Semver) -> bool {
+        let after_start = self
+            .introduced
+            .as_ref()
+            .is_none_or(|introduced| version >= introduced);
+        let before_end = match (&self.fixed, &self.last_affected) {
+            (Some(fixed), _) => version < fixed,
+            (None, Some(last_affected)) => version <= last_affected,
+            (None, None) => true,
+        };
+        after_start && before_end
+    }
+}
+
+impl Advisory {
+    /// Returns true if the version is affected by the advisory.
+    pub fn affects(&self, version: &Semver) -> bool {
+        match &self.affected {
+            Affected::Intervals { intervals, versions } => {
+                intervals.iter().any(|interval| interval.contains(version))
+                    || versions.iter().any(|affected| *affected == version.to_string())
+            }
+            Affected::Range { range, .. } => range.satisfies(version),
+        }
+    }
+
+    /// Returns the first version that fixes the advisory for the affected version.
+    pub fn patched(&self, version: &Semver) -> Option<String> {
+        match &self.affected {
+            Affected::Intervals { intervals, .. } => intervals
+                .iter()
+                .filter(|interval| interval.contains(version))
+                .filter_map(|interval| interval.fixed.as_ref())
+                .min()
+                .map(|fixed| fixed.to_string()),
+            Affected::Range { patched, .. } => {
+                let patched = patched.as_ref()?;
+                patched
+                    .min_versions()
+                    .into_iter()
+                    .filter(|first| *first > version && patched.satisfies(first))
+                    .min()
+                    .map(|first| first.to_string())
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct OsvAdvisory {
+    id: String,
+
+    #[serde(default)]
+    summary: String,
+
+    #[serde(default)]
+    affected: Vec<OsvAffected>,
+
+    database_specific: Option<OsvDatabaseSpecific>,
+    withdrawn: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct OsvAffected {
+    package: OsvPackage,
+
+    #[serde(default)]
+    ranges: Vec<OsvRange>,
+
+    #[serde(default)]
+    versions: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct OsvPackage {
+    ecosystem: String,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct OsvRange {
+    #[serde(rename = "type")]
+    kind: String,
+    events: Vec<OsvEvent>,
+}
+
+#[derive(Deserialize)]
+struct OsvEvent {
+    introduced: Option<String>,
+    fixed: Option<String>,
+    last_affected: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct OsvDatabaseSpecific {
+    severity: Option<String>,
+}
+
+/// An entry in npm's bulk advisory format, `{ "<name>": [<advisory>, ...] }`.
+#[derive(Deserialize)]
+struct NpmAdvisory {
+    id: Value,
+
+    #[serde(default)]
+    title: String,
+
+    #[serde(default)]
+    severity: String,
+
+    vulnerable_versions: String,
+    patched_versions: Option<String>,
+}
+
+/// The advisories in a local copy of the GitHub Advisory Database, or any folder of OSV or npm
+/// bulk advisory JSON files, keyed by package name.
+#[derive(Debug, Default)]
+pub struct AdvisoryDatabase {
+    pub advisories: HashMap<String, Vec<Advisory>>,
+}
+
+impl AdvisoryDatabase {
+    /// Returns the AdvisoryDatabase type for every JSON file in the folder and its subfolders,
+    /// skipping files that are not advisories with a warning.
+    pub fn from(path: &Path) -> Result<Self, Error> {
+        let mut database = AdvisoryDatabase::default();
+        let mut dirs = vec![path.to_path_buf()];
+
+        while let Some(dir) = dirs.pop() {
+            for entry in fs::read_dir(&dir)?.flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    dirs.push(path);
+                } else if path.extension().is_some_and(|extension| extension == "json") {
+                    if let Err(err) = database.parse(&fs::read_to_string(&path)?) {
+                        eprintln!("Skipping {}, which is not an advisory file: {}", path.display(), err);
+                    }
+                }
+            }
+        }
+
+        Ok(database)
+    }
+
+    /// Adds the advisories in the contents of an OSV or npm bulk advisory file. Nothing is added
+    /// if any of the file's advisories can't be read.
+    pub fn parse(&mut self, contents: &str) -> Result<(), Error> {
+        let json: Value = serde_json::from_str(contents)?;
+        if json.get("id").is_some_and(Value::is_string) {
+            self.add_osv(serde_json::from_value(json)?);
+        } else {
+            let bulk: HashMap<String, Vec<NpmAdvisory>> = serde_json::from_value(json)?;
+            let mut advisories = Vec::new();
+            for (name, entries) in bulk {
+                for entry in entries {
+                    advisories.push((name.clone(), get_npm_advisory(entry)?));
+                }
+            }
+            for (name, advisory) in advisories {
+                self.advisories.entry(name).or_default().push(advisory);
+            }
+        }
+        Ok(())
+    }
+
+    fn add_osv(&mut self, osv: OsvAdvisory) {
+        if osv.withdrawn.is_some() {
+            return;
+        }
+        let severity = osv
+            .database_specific
+            .and_then(|specific| specific.severity)
+            .map_or(Severity::Unknown, |severity| Severity::from(&severity));
+
+        for affected in osv.affected {
+            if affected.package.ecosystem != "npm" {
+                continue;
+            }
+
+            let mut intervals = Vec::new();
+            for range in affected.ranges.iter().filter(|range| range.kind != "GIT") {
+                let mut open: Option<Option<Semver>> = None;
+                for event in range.events.iter() {
+                    if let Some(introduced) = &event.introduced {
+                        open = Some(Semver::from(introduced.clone()));
+                    } else if event.fixed.is_some() || event.last_affected.is_some() {
+                        intervals.push(Interval {
+                            introduced: open.take().flatten(),
+                            fixed: event.fixed.clone().and_then(Semver::from),
+                            last_affected: event.last_affected.clone().and_then(Semver::from),
+                        });
+                    }
+                }
+                if let Some(introduced) = open {
+                    intervals.push(Interval {
+                        introduced,
+                        fixed: None,
+                        last_affected: None,
+                    });
+                }
+            }
+
+            self.advisories
+                .entry(affected.package.name)
+                .or_default()
+                .push(Advisory {
+                    id: osv.id.clone(),
+                    summary: osv.summary.clone(),
+                    severity,
+                    affected: Affected::Intervals {
+                        intervals,
+                        versions: affected.versions,
+                    },
+                });
+        }
+    }
+
+}
+
+fn get_npm_advisory(npm: NpmAdvisory) -> Result<Advisory, Error> {
+    let range = Range::parse(&npm.vulnerable_versions).ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("invalid vulnerable_versions \"{}\"", npm.vulnerable_versions),
+        )
+    })?;
+    let id = match npm.id {
+        Value::String(id) => id,
+        id => id.to_string(),
+    };
+    Ok(Advisory {
+        id,
+        summary: npm.title,
+        severity: Severity::from(&npm.severity),
+        affected: Affected::Range {
+            range,
+            patched: npm.patched_versions.as_deref().and_then(Range::parse),
+        },
+    })
+}
+
+/// An installed package and the chain of packages that brings it into the project.
+#[derive(Clone)]
+pub struct InstalledPackage {
+    pub name: String,
+    pub version: Semver,
+    pub path: Vec<String>,
+}
+
+impl InstalledPackage {
+    /// Returns every package in the lockfile, including nested ones, with the shortest path to
+    /// each from the project's own dependencies.
+    pub fn from_lockfile(lockfile: &Lockfile, roots: &[String]) -> Vec<Self> {
+        let mut chains = lockfile.dependency_paths(roots);
+        lockfile
+            .packages
+            .iter()
+            .filter_map(|(path, locked)| {
+                let version = Semver::from(locked.version.clone()?)?;
+                let chain = chains.remove(path).unwrap_or_else(|| vec![path.clone()]);
+                Some(InstalledPackage {
+                    name: get_name_from_lock_path(path).to_string(),
+                    version,
+                    path: chain
+                        .iter()
+                        .map(|path| get_name_from_lock_path(path).to_string())
+                        .collect(),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the top-level installed packages.
+    pub fn from_modules(dependencies: &[StandardModule]) -> Vec<Self> {
+        dependencies
+            .iter()
+            .filter_map(|dependency| {
+                Some(InstalledPackage {
+                    name: dependency.name.clone(),
+                    version: dependency.version.clone()?,
+                    path: vec![dependency.name.clone()],
+                })
+            })
+            .collect()
+    }
+}
+
+/// An installed package affected by an advisory.
+pub struct AdvisoryFinding<'a> {
+    pub package: InstalledPackage,
+    pub advisory: &'a Advisory,
+    pub patched: Option<String>,
+}
+
+/// Returns the advisories affecting each package, the most severe first.
+pub fn audit(packages: Vec<InstalledPackage>, database: &AdvisoryDatabase) -> Vec<AdvisoryFinding<'_>> {
+    let mut findings = Vec::new();
+
+    for package in packages {
+        let advisories = match database.advisories.get(&package.name) {
+            Some(advisories) => advisories,
+            None => continue,
+        };
+        for advisory in advisories.iter().filter(|advisory| advisory.affects(&package.version)) {
+            findings.push(AdvisoryFinding {
+                patched: advisory.patched(&package.version),
+                package: package.clone(),
+                advisory,
+            });
+        }
+    }
+
+    findings.sort_by(|a, b| {
+        b.advisory
+            .severity
+            .cmp(&a.advisory.severity)
+            .then(a.package.name.cmp(&b.package.name))
+            .then(a.package.version.cmp(&b.package.version))
+            .then(a.advisory.id.cmp(&b.advisory.id))
+    });
+    findings
+}
+
+impl<'a> PrintTable for AdvisoryFinding<'a> {
+    fn table_row(&self) -> Row {
+        let colour = match self.advisory.severity {
+            Severity::Critical | Severity::High => color::RED,
+            Severity::Moderate => color::YELLOW,
+            Severity::Low | Severity::Unknown => color::WHITE,
+        };
+        Row::new(vec![
+            new_cell(&self.package.name),
+            new_cell(&self.package.version.to_string()),
+            new_cell(self.advisory.severity.name()).with_style(Attr::ForegroundColor(colour)),
+            new_cell(&self.advisory.id),
+            new_cell(self.patched.as_deref().unwrap_or("no fix")),
+            new_cell(&self.package.path.join(" > ")),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::{write, TestDir};
+
+    fn version(version: &str) -> Semver {
+        Semver::from(String::from(version)).unwrap()
+    }
+
+    #[test]
+    fn matches_osv_advisories() {
+        let mut database = AdvisoryDatabase::default();
+        database
+            .parse(
+                r#"{
+                    "id": "GHSA-aaaa-bbbb-cccc",
+                    "summary": "Prototype pollution",
+                    "affected": [{
+                        "package": { "ecosystem": "npm", "name": "a" },
+                        "ranges": [{
+                            "type": "ECOSYSTEM",
+                            "events": [
+                                { "introduced": "0" }, { "fixed": "1.2.0" },
+                                { "introduced": "2.0.0" }, { "last_affected": "2.1.0" }
+                            ]
+                        }],
+                        "versions": ["3.0.0"]
+                    }, {
+                        "package": { "ecosystem": "PyPI", "name": "b" }
+                    }],
+                    "database_specific": { "severity": "HIGH" }
+                }"#,
+            )
+            .unwrap();
+
+        let advisory = &database.advisories["a"][0];
+        assert_eq!(advisory.severity, Severity::High);
+        assert!(advisory.affects(&version("1.1.9")));
+        assert_eq!(advisory.patched(&version("1.1.9")).as_deref(), Some("1.2.0"));
+        assert!(!advisory.affects(&version("1.2.0")));
+        assert!(advisory.affects(&version("2.1.0")));
+        assert_eq!(advisory.patched(&version("2.1.0")), None);
+        assert!(!advisory.affects(&version("2.1.1")));
+        assert!(advisory.affects(&version("3.0.0")));
+        assert!(!database.advisories.contains_key("b"));
+    }
+
+    #[test]
+    fn matches_npm_bulk_advisories() {
+        let mut database = AdvisoryDatabase::default();
+        database
+            .parse(
+                r#"{
+                    "a": [{
+                        "id": 1523,
+                        "title": "Prototype pollution",
+                        "severity": "moderate",
+                        "vulnerable_versions": "<4.17.21",
+                        "patched_versions": ">=4.17.21"
+                    }],
+                    "b": [{
+                        "id": 1600,
+                        "vulnerable_versions": "<1.2.3 || >=2.0.0 <2.1.4",
+                        "patched_versions": ">=1.2.3 <2.0.0 || >=2.1.4"
+                    }, {
+                        "id": 1601,
+                        "vulnerable_versions": ">=0.0.0",
+                        "patched_versions": "<0.0.0"
+                    }]
+                }"#,
+            )
+            .unwrap();
+
+        let packages = vec![
+            InstalledPackage {
+                name: String::from("a"),
+                version: version("4.17.20"),
+                path: vec![String::from("b"), String::from("a")],
+            },
+            InstalledPackage {
+                name: String::from("a"),
+                version: version("4.17.21"),
+                path: vec![String::from("a")],
+            },
+        ];
+        let findings = audit(packages, &database);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].advisory.id, "1523");
+        assert_eq!(findings[0].advisory.severity, Severity::Moderate);
+        assert_eq!(findings[0].patched.as_deref(), Some("4.17.21"));
+        assert_eq!(findings[0].package.path, vec!["b", "a"]);
+
+        let advisories = &database.advisories["b"];
+        assert_eq!(advisories[0].patched(&version("1.0.0")).as_deref(), Some("1.2.3"));
+        assert_eq!(advisories[0].patched(&version("2.0.5")).as_deref(), Some("2.1.4"));
+        assert_eq!(advisories[1].patched(&version("2.0.5")), None);
+    }
+
+    #[test]
+    fn skips_files_that_are_not_advisories() {
+        let root = TestDir::new("advisories");
+        write(root.join("a.json"), r#"{ "a": [{ "id": 1, "vulnerable_versions": "<1.0.0" }] }"#);
+        write(root.join("package.json"), r#"{ "name": "advisory-db", "version": "1.0.0" }"#);
+        write(root.join("README.md"), "# Advisories");
+
+        let database = AdvisoryDatabase::from(&root).unwrap();
+        assert_eq!(database.advisories.len(), 1);
+        assert!(database.advisories["a"][0].affects(&version("0.9.0")));
+
+        let mut partial = AdvisoryDatabase::default();
+        assert!(partial
+            .parse(r#"{ "a": [{ "id": 1, "vulnerable_versions": "<1.0.0" }, { "id": 2, "vulnerable_versions": "not a range" }] }"#)
+            .is_err());
+        assert!(partial.advisories.is_empty());
+    }
+}
@@ -4,6 +4,9 @@ use once_cell::sync::OnceCell;
 
 #[derive(StructOpt)]
 pub struct Cli {
+    #[structopt(subcommand)]
+    pub command: Option<Command>,
+
     /// The pattern to filter folders in node_modules.
     #[structopt(default_value = ".*")]
     pub filter: String,
@@ -12,13 +15,50 @@ pub struct Cli {
     #[structopt(long, parse(from_os_str), default_value = ".")]
     pub path: PathBuf,
 
-    #[structopt(long)]
-    pub diff: Option<PathBuf>,
+    /// The project to compare with, or with -g a global folder, npm prefix or Node installation
+    /// version such as `18` or `nvm:18.19.0`. Given twice, compares the two with each other.
+    #[structopt(long, parse(from_os_str), raw(number_of_values = "1"))]
+    pub diff: Vec<PathBuf>,
+
+    /// Compare the dependencies locked at a git revision with the working tree, or at two
+    /// revisions written as `A..B`, using the package.json and lockfile committed at each. A
+    /// missing side of `A..B` is HEAD.
+    #[structopt(long = "diff-rev")]
+    pub diff_rev: Option<String>,
 
     /// Search in global dependencies.
     #[structopt(long, short = "g")]
     pub global: bool,
 
+    /// The package manager whose global packages to show with -g. Defaults to npm, or with
+    /// --import to the manager the manifest was exported from.
+    #[structopt(long = "global-manager", raw(possible_values = r#"&["npm", "yarn", "pnpm"]"#))]
+    pub global_manager: Option<String>,
+
+    /// With -g, show each package's executables, whether they are linked into the global bin
+    /// folder and whether another executable comes before them on PATH.
+    #[structopt(long, raw(requires = r#""global""#))]
+    pub bins: bool,
+
+    /// With -g, write the names, versions and install specs of the global packages to a
+    /// manifest file.
+    #[structopt(long, parse(from_os_str), raw(requires = r#""global""#))]
+    pub export: Option<PathBuf>,
+
+    /// With -g, compare a manifest written by --export with the global packages and print the
+    /// commands that install what is missing or at a different version.
+    #[structopt(long, parse(from_os_str), raw(requires = r#""global""#))]
+    pub import: Option<PathBuf>,
+
+    /// With --import, write the install commands to a shell script instead of printing them.
+    #[structopt(long, parse(from_os_str), raw(requires = r#""import""#))]
+    pub script: Option<PathBuf>,
+
+    /// Compare the global packages that are also installed in the project with the project's
+    /// copies.
+    #[structopt(long = "compare-global")]
+    pub compare_global: bool,
+
     /// Show dependencies.
     #[structopt(long, short = "D" )] 
     pub dep: bool,
@@ -27,11 +67,228 @@ pub struct Cli {
     #[structopt(long, short = "d")]
     pub dev: bool,
 
+    /// Only show packages that are symlinked into node_modules.
+    #[structopt(long, short = "l")]
+    pub linked: bool,
+
+    /// Show where each package was installed from.
+    #[structopt(long, short = "p")]
+    pub provenance: bool,
+
+    /// Show the newest version of each package in the npm cache, without any network access.
+    #[structopt(long)]
+    pub cached: bool,
+
+    /// The pattern to filter packages by where they were installed from, e.g. "^(private|git)".
+    #[structopt(long)]
+    pub source: Option<String>,
+
+    /// A registry host to treat like the public npm registry rather than as private, e.g.
+    /// "npm.ourco.com", which `--registry-audit` also accepts for internal packages. Can be
+    /// repeated.
+    #[structopt(long = "approved-registry", raw(number_of_values = "1"))]
+    pub approved_registries: Vec<String>,
+
+    /// Check that packages were resolved from the registries configured in .npmrc.
+    #[structopt(long = "registry-audit")]
+    pub registry_audit: bool,
+
+    /// A file listing internal package names, one per line, for --registry-audit.
+    #[structopt(long = "internal-packages", parse(from_os_str))]
+    pub internal_packages: Option<PathBuf>,
+
     /// Include build metadata in versions
     #[structopt(long, short = "m")]
     pub meta: bool,
 }
 
+#[derive(StructOpt)]
+pub enum Command {
+    /// Show the versions available from the registry for each package.
+    #[structopt(name = "outdated")]
+    Outdated {
+        /// The maximum number of requests to make to the registry at once.
+        #[structopt(long, default_value = "8")]
+        concurrency: usize,
+
+        /// The number of seconds to wait for each request to the registry.
+        #[structopt(long, default_value = "30")]
+        timeout: u64,
+
+        /// Use the packuments in the npm cache instead of the registry.
+        #[structopt(long)]
+        offline: bool,
+
+        /// The npm cache folder to use with --offline, defaulting to ~/.npm.
+        #[structopt(long, parse(from_os_str))]
+        cache: Option<PathBuf>,
+    },
+
+    /// Show how far behind the latest release each package is, in days and releases.
+    #[structopt(name = "freshness")]
+    Freshness {
+        /// The maximum number of requests to make to the registry at once.
+        #[structopt(long, default_value = "8")]
+        concurrency: usize,
+
+        /// The number of seconds to wait for each request to the registry.
+        #[structopt(long, default_value = "30")]
+        timeout: u64,
+
+        /// A folder of <name>.json packuments to use instead of the registry.
+        #[structopt(long, parse(from_os_str))]
+        packuments: Option<PathBuf>,
+    },
+
+    /// Group packages by license and check them against a policy.
+    #[structopt(name = "licenses")]
+    Licenses {
+        /// A JSON file with "allow" and/or "deny" lists of SPDX license identifiers.
+        #[structopt(long, parse(from_os_str))]
+        policy: Option<PathBuf>,
+    },
+
+    /// Write the license and notice files of production dependencies to one document.
+    #[structopt(name = "notices")]
+    Notices {
+        /// The file to write to, instead of stdout.
+        #[structopt(long, short = "o", parse(from_os_str))]
+        output: Option<PathBuf>,
+
+        /// Write Markdown instead of plain text.
+        #[structopt(long)]
+        markdown: bool,
+    },
+
+    /// Match installed versions against a local database of security advisories.
+    #[structopt(name = "advisories")]
+    Advisories {
+        /// A folder of OSV advisories, such as a clone of the GitHub Advisory Database, or of
+        /// npm bulk advisory JSON files.
+        #[structopt(long, parse(from_os_str))]
+        database: PathBuf,
+    },
+
+    /// Show the disk space taken by each dependency and everything it depends on.
+    #[structopt(name = "size")]
+    Size {
+        /// List every installed package, including nested ones, instead of each dependency's
+        /// subtree.
+        #[structopt(long)]
+        packages: bool,
+
+        /// The order to list packages in.
+        #[structopt(long, default_value = "size", raw(possible_values = r#"&["size", "name"]"#))]
+        sort: String,
+    },
+
+    /// Show packages whose engines ranges exclude the Node and npm versions they will run on.
+    #[structopt(name = "engines")]
+    Engines {
+        /// The Node version to check against, instead of the node on PATH, e.g. `22`, `22.1` or
+        /// `22.1.0`. A version without a minor or patch number means its first release.
+        #[structopt(long = "target-node")]
+        target_node: Option<String>,
+
+        /// The npm version to check against, instead of the npm on PATH, written like --target-node.
+        #[structopt(long = "target-npm")]
+        target_npm: Option<String>,
+    },
+
+    /// Compare the Node and package manager versions the project pins with those on PATH.
+    #[structopt(name = "toolchain")]
+    Toolchain,
+
+    /// Compare the versions of packages across several projects, one column per project.
+    #[structopt(name = "matrix")]
+    Matrix {
+        /// The projects to compare.
+        #[structopt(parse(from_os_str), raw(required = "true"))]
+        projects: Vec<PathBuf>,
+
+        /// Only show packages whose versions differ between projects.
+        #[structopt(long)]
+        inconsistent: bool,
+    },
+
+    /// Find every project under a folder and list the packages they use.
+    #[structopt(name = "scan")]
+    Scan {
+        /// The folder to search for package.json files.
+        #[structopt(parse(from_os_str))]
+        root: PathBuf,
+
+        /// A package to look for, optionally with a range, e.g. `moment` or `lodash@<4.17.21`.
+        /// Lists every package when not given.
+        #[structopt(long, short = "q", raw(number_of_values = "1"))]
+        query: Vec<String>,
+
+        /// How to write the results.
+        #[structopt(long, default_value = "table", raw(possible_values = r#"&["table", "json", "csv"]"#))]
+        format: String,
+
+        /// The file to write JSON or CSV to, instead of stdout.
+        #[structopt(long, short = "o", parse(from_os_str))]
+        output: Option<PathBuf>,
+    },
+
+    /// Write a changelog of the dependencies added, removed, upgraded and downgraded between
+    /// two lockfiles, snapshots, project folders or git revisions.
+    #[structopt(name = "release-notes")]
+    ReleaseNotes {
+        /// The lockfile, snapshot, project folder or git revision to compare from.
+        from: String,
+
+        /// What to compare with, defaulting to the project's lockfile.
+        to: Option<String>,
+
+        /// Only include production dependencies.
+        #[structopt(long)]
+        production: bool,
+
+        /// Write Markdown instead of plain text.
+        #[structopt(long)]
+        markdown: bool,
+
+        /// The file to write to, instead of stdout.
+        #[structopt(long, short = "o", parse(from_os_str))]
+        output: Option<PathBuf>,
+    },
+
+    /// Show the commits that changed a package's declared range or locked version.
+    #[structopt(name = "history")]
+    History {
+        /// The package to show the history of.
+        package: String,
+    },
+
+    /// Save the installed packages to a file, or compare them with a file saved earlier.
+    #[structopt(name = "snapshot")]
+    Snapshot(SnapshotCommand),
+
+    /// List the Node installations managed by nvm, fnm, Volta and asdf, with their global
+    /// packages.
+    #[structopt(name = "installations")]
+    Installations,
+}
+
+#[derive(StructOpt)]
+pub enum SnapshotCommand {
+    /// Record the name, version, path, dependency type and source of each installed package.
+    #[structopt(name = "save")]
+    Save {
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+    },
+
+    /// Compare the installed packages with a snapshot.
+    #[structopt(name = "diff")]
+    Diff {
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+    },
+}
+
 static INSTANCE: OnceCell<Cli> = OnceCell::new();
 
 impl Cli {
@@ -40,4 +297,9 @@ impl Cli {
             Cli::from_args()
         })
     }
+
+    /// Returns the --global-manager name, defaulting to npm.
+    pub fn global_manager(&self) -> &str {
+        self.global_manager.as_deref().unwrap_or("npm")
+    }
 }
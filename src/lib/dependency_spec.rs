@@ -0,0 +1,318 @@
+use std::fmt;
+
+use regex::Regex;
+
+use crate::range::Range;
+use crate::semver::Semver;
+
+/// A dependency as declared in a package.json, covering every kind of spec npm accepts.
+#[derive(Debug, PartialEq)]
+pub enum DependencySpec {
+    /// A single version, optionally prefixed with an operator such as `^` or `~`.
+    Version(Semver),
+    /// Any other semver range, e.g. `>=1.2.0 <2` or `1.x || 2.x`.
+    Range(String),
+    /// A dist-tag such as `latest` or `next`.
+    Tag(String),
+    /// An `npm:` alias installing `name` under a different folder name.
+    Alias { name: String, spec: Box<DependencySpec> },
+    /// A git repository, including GitHub/GitLab/Bitbucket shorthands.
+    Git { repo: String, committish: Option<String> },
+    /// A `file:` spec or a bare local path.
+    File(String),
+    /// A `link:` spec.
+    Link(String),
+    /// A remote tarball URL.
+    Tarball(String),
+    /// A `workspace:` protocol spec.
+    Workspace(String),
+}
+
+impl DependencySpec {
+    /// Returns the DependencySpec for a declared version string.
+    pub fn from(spec: &str) -> Self {
+        let spec = spec.trim();
+
+        if let Some(rest) = spec.strip_prefix("npm:") {
+            return Self::alias(rest);
+        }
+        if let Some(rest) = spec.strip_prefix("workspace:") {
+            return DependencySpec::Workspace(rest.to_string());
+        }
+        if let Some(rest) = spec.strip_prefix("file:") {
+            return DependencySpec::File(rest.to_string());
+        }
+        if let Some(rest) = spec.strip_prefix("link:") {
+            return DependencySpec::Link(rest.to_string());
+        }
+        if let Some(git) = Self::git(spec) {
+            return git;
+        }
+        if spec.starts_with("http://") || spec.starts_with("https://") {
+            return DependencySpec::Tarball(spec.to_string());
+        }
+        if is_local_path(spec) {
+            return DependencySpec::File(spec.to_string());
+        }
+
+        let version_re = Regex::new(
+            r#"^[~>=<^]*v?\d+\.\d+\.\d+(?:-[.\-0-9a-zA-Z]+)?(?:\+[.\-0-9a-zA-Z]+)?$"#,
+        )
+        .unwrap();
+        if version_re.is_match(spec) {
+            if let Some(version) = Semver::from(spec.to_string()) {
+                return DependencySpec::Version(version);
+            }
+        }
+
+        let tag_re = Regex::new(r#"^[a-zA-Z][a-zA-Z0-9._\-]*$"#).unwrap();
+        if tag_re.is_match(spec) && !spec.eq_ignore_ascii_case("x") {
+            return DependencySpec::Tag(spec.to_string());
+        }
+
+        DependencySpec::Range(spec.to_string())
+    }
+
+    /// Returns the name of the package that is actually installed for this spec, if it differs
+    /// from the name it was declared under.
+    pub fn aliased_name(&self) -> Option<&str> {
+        match self {
+            DependencySpec::Alias { name, .. } => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the spec is fetched from a registry rather than a git host, tarball or
+    /// local path.
+    pub fn is_registry(&self) -> bool {
+        match self {
+            DependencySpec::Version(_) | DependencySpec::Range(_) | DependencySpec::Tag(_) => true,
+            DependencySpec::Alias { spec, .. } => spec.is_registry(),
+            _ => false,
+        }
+    }
+
+    /// Returns the version when the spec pins (or starts from) a single version.
+    pub fn version(&self) -> Option<&Semver> {
+        match self {
+            DependencySpec::Version(version) => Some(version),
+            DependencySpec::Alias { spec, .. } => spec.version(),
+            _ => None,
+        }
+    }
+
+    /// Returns the semver range the spec accepts, for specs that are resolved from a range.
+    pub fn range(&self) -> Option<Range> {
+        match self {
+            DependencySpec::Version(version) => Range::parse(&version.to_string()),
+            DependencySpec::Range(range) => Range::parse(range),
+            DependencySpec::Alias { spec, .. } => spec.range(),
+            _ => None,
+        }
+    }
+
+    fn alias(rest: &str) -> Self {
+        let split_at = match rest.starts_with('@') {
+            true => rest[1..].find('@').map(|i| i + 1),
+            false => rest.find('@'),
+        };
+        let (name, spec) = match split_at {
+            Some(i) => (&rest[..i], DependencySpec::from(&rest[i + 1..])),
+            None => (rest, DependencySpec::Tag(String::from("latest"))),
+        };
+        DependencySpec::Alias {
+            name: name.to_string(),
+            spec: Box::new(spec),
+        }
+    }
+
+    fn git(spec: &str) -> Option<Self> {
+        let (location, committish) = match spec.find('#') {
+            Some(i) => (&spec[..i], Some(spec[i + 1..].to_string())),
+            None => (spec, None),
+        };
+
+        let hosted = [
+            ("github:", "github.com/"),
+            ("gitlab:", "gitlab.com/"),
+            ("bitbucket:", "bitbucket.org/"),
+            ("gist:", "gist.github.com/"),
+        ];
+        for (prefix, host) in hosted.iter() {
+            if let Some(path) = location.strip_prefix(prefix) {
+                return Some(DependencySpec::Git {
+                    repo: format!("{}{}", host, path.trim_end_matches(".git")),
+                    committish,
+                });
+            }
+        }
+
+        let is_url_git = location.starts_with("git+")
+            || location.starts_with("git://")
+            || location.starts_with("git@")
+            || ((location.starts_with("http://") || location.starts_with("https://"))
+                && location.ends_with(".git"));
+        if is_url_git {
+            return Some(DependencySpec::Git {
+                repo: normalise_git_url(location),
+                committish,
+            });
+        }
+
+        let shorthand_re = Regex::new(r#"^[^@./:~\s][^/:\s]*/[^/:\s]+$"#).unwrap();
+        if shorthand_re.is_match(location) {
+            return Some(DependencySpec::Git {
+                repo: format!("github.com/{}", location.trim_end_matches(".git")),
+                committish,
+            });
+        }
+
+        None
+    }
+}
+
+impl fmt::Display for DependencySpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DependencySpec::Version(version) => write!(f, "{}", version),
+            DependencySpec::Range(range) => write!(f, "{}", range),
+            DependencySpec::Tag(tag) => write!(f, "{}", tag),
+            DependencySpec::Alias { name, spec } => write!(f, "alias → {}@{}", name, spec),
+            DependencySpec::Git { repo, committish } => match committish {
+                Some(committish) => write!(f, "git: {}#{}", repo, committish),
+                None => write!(f, "git: {}", repo),
+            },
+            DependencySpec::File(path) => write!(f, "file: {}", path),
+            DependencySpec::Link(path) => write!(f, "link: {}", path),
+            DependencySpec::Tarball(url) => write!(f, "tarball: {}", url),
+            DependencySpec::Workspace(range) => write!(f, "workspace: {}", range),
+        }
+    }
+}
+
+fn is_local_path(spec: &str) -> bool {
+    spec.starts_with("./")
+        || spec.starts_with("../")
+        || spec.starts_with('/')
+        || spec.starts_with("~/")
+        || spec == "."
+        || spec == ".."
+}
+
+/// Strips the protocol, credentials and `.git` suffix so repositories read as `host/path`.
+fn normalise_git_url(url: &str) -> String {
+    let url = url.strip_prefix("git+").unwrap_or(url);
+    let url = match url.find("://") {
+        Some(i) => &url[i + 3..],
+        None => url,
+    };
+    let url = match url.find('@') {
+        Some(i) if !url[..i].contains('/') => &url[i + 1..],
+        _ => url,
+    };
+    // scp-style `host:path` locations have no port, so the first colon separates the path.
+    let url = match url.find(':') {
+        Some(i) if !url[i + 1..].starts_with(|c: char| c.is_ascii_digit()) => {
+            format!("{}/{}", &url[..i], &url[i + 1..])
+        }
+        _ => url.to_string(),
+    };
+    url.trim_end_matches(".git").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn git(repo: &str, committish: Option<&str>) -> DependencySpec {
+        DependencySpec::Git {
+            repo: repo.to_string(),
+            committish: committish.map(String::from),
+        }
+    }
+
+    #[test]
+    fn parses_versions_and_ranges() {
+        assert_eq!(
+            DependencySpec::from("^8.1.2"),
+            DependencySpec::Version(Semver::from(String::from("^8.1.2")).unwrap())
+        );
+        assert_eq!(
+            DependencySpec::from(">=1.2.0 <2"),
+            DependencySpec::Range(String::from(">=1.2.0 <2"))
+        );
+        assert_eq!(DependencySpec::from("*"), DependencySpec::Range(String::from("*")));
+        assert_eq!(DependencySpec::from("1.x"), DependencySpec::Range(String::from("1.x")));
+        assert_eq!(DependencySpec::from("next"), DependencySpec::Tag(String::from("next")));
+    }
+
+    #[test]
+    fn handles_git_string() {
+        assert_eq!(
+            DependencySpec::from("git+ssh://git@github.com/my-package.git#semver:~6.1.5"),
+            git("github.com/my-package", Some("semver:~6.1.5"))
+        );
+        assert_eq!(
+            DependencySpec::from("git@github.com:org/repo.git"),
+            git("github.com/org/repo", None)
+        );
+        assert_eq!(
+            DependencySpec::from("https://gitlab.com/org/repo.git#main"),
+            git("gitlab.com/org/repo", Some("main"))
+        );
+    }
+
+    #[test]
+    fn handles_hosted_shorthands() {
+        assert_eq!(DependencySpec::from("org/repo#v2"), git("github.com/org/repo", Some("v2")));
+        assert_eq!(DependencySpec::from("github:org/repo"), git("github.com/org/repo", None));
+        assert_eq!(
+            DependencySpec::from("bitbucket:org/repo"),
+            git("bitbucket.org/org/repo", None)
+        );
+        assert_eq!(
+            DependencySpec::from("org/repo#v2").to_string(),
+            "git: github.com/org/repo#v2"
+        );
+    }
+
+    #[test]
+    fn handles_local_specs() {
+        assert_eq!(
+            DependencySpec::from("file:.yalc/@registry/my-package"),
+            DependencySpec::File(String::from(".yalc/@registry/my-package"))
+        );
+        assert_eq!(
+            DependencySpec::from("../my-package"),
+            DependencySpec::File(String::from("../my-package"))
+        );
+        assert_eq!(
+            DependencySpec::from("link:../my-package"),
+            DependencySpec::Link(String::from("../my-package"))
+        );
+        assert_eq!(
+            DependencySpec::from("workspace:^"),
+            DependencySpec::Workspace(String::from("^"))
+        );
+    }
+
+    #[test]
+    fn handles_tarballs() {
+        let url = "https://registry.example.com/pkg/-/pkg-1.0.0.tgz";
+        assert_eq!(DependencySpec::from(url), DependencySpec::Tarball(url.to_string()));
+    }
+
+    #[test]
+    fn handles_aliases() {
+        let alias = DependencySpec::from("npm:other@^2.0.0");
+        assert_eq!(alias.aliased_name(), Some("other"));
+        assert_eq!(alias.to_string(), "alias → other@^2.0.0");
+
+        let scoped = DependencySpec::from("npm:@scope/other@1.0.0");
+        assert_eq!(scoped.aliased_name(), Some("@scope/other"));
+        assert_eq!(scoped.version(), Semver::from(String::from("1.0.0")).as_ref());
+
+        let bare = DependencySpec::from("npm:other");
+        assert_eq!(bare.to_string(), "alias → other@latest");
+    }
+}
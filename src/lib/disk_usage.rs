@@ -0,0 +1,315 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, Metadata};
+use std::io::Error;
+use std::path::{Path, PathBuf};
+
+use prettytable::Row;
+use serde::Deserialize;
+
+use crate::lockfile::resolve_install_path;
+use crate::node_module::{new_cell, PrintTable};
+
+/// The space taken by one installed package, not counting the packages nested in its own
+/// node_modules folder.
+#[derive(Debug, Default)]
+pub struct PackageSize {
+    pub name: String,
+    /// The install path relative to the project, e.g. `node_modules/a/node_modules/b`.
+    pub install_path: String,
+    pub bytes: u64,
+    pub files: u64,
+    dependencies: Vec<String>,
+}
+
+/// The space taken by a dependency of the project and everything it depends on.
+#[derive(Debug, Default, PartialEq)]
+pub struct SubtreeSize {
+    pub name: String,
+    pub bytes: u64,
+    pub files: u64,
+    /// The packages in the subtree, including the dependency itself.
+    pub packages: usize,
+    pub inclusive_bytes: u64,
+    /// The space taken by packages that no other dependency of the project needs, which is what
+    /// removing the dependency would free.
+    pub exclusive_bytes: u64,
+}
+
+/// The dependency fields used to follow a package's subtree.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DependencyFields {
+    #[serde(default)]
+    dependencies: HashMap<String, String>,
+
+    #[serde(default)]
+    optional_dependencies: HashMap<String, String>,
+
+    #[serde(default)]
+    peer_dependencies: HashMap<String, String>,
+}
+
+/// The on-disk size of every package in a node_modules folder, keyed by install path.
+#[derive(Debug, Default)]
+pub struct DiskUsage {
+    pub packages: HashMap<String, PackageSize>,
+}
+
+/// The files and folders already counted, so hardlinked files and folders reached through more
+/// than one symlink are only counted once.
+#[derive(Default)]
+struct Seen {
+    files: HashSet<(u64, u64)>,
+    folders: HashSet<PathBuf>,
+}
+
+impl DiskUsage {
+    /// Returns the DiskUsage type for the node_modules folder of the project at `base_path`.
+    pub fn from(base_path: &Path) -> Result<Self, Error> {
+        let mut usage = DiskUsage::default();
+        usage.collect(&base_path.join("node_modules"), "", &mut Seen::default())?;
+        Ok(usage)
+    }
+
+    fn collect(&mut self, node_modules: &Path, prefix: &str, seen: &mut Seen) -> Result<(), Error> {
+        if !node_modules.is_dir() {
+            return Ok(());
+        }
+
+        for entry in fs::read_dir(node_modules)?.flatten() {
+            let folder_name = entry.file_name().to_string_lossy().to_string();
+            if folder_name.starts_with('.') {
+                continue;
+            }
+
+            let path = entry.path();
+            if folder_name.starts_with('@') && path.is_dir() {
+                for scoped in fs::read_dir(&path)?.flatten() {
+                    let name = format!("{}/{}", folder_name, scoped.file_name().to_string_lossy());
+                    self.add_package(&scoped.path(), &name, prefix, seen)?;
+                }
+            } else {
+                self.add_package(&path, &folder_name, prefix, seen)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn add_package(&mut self, path: &Path, name: &str, prefix: &str, seen: &mut Seen) -> Result<(), Error> {
+        // Dangling links and stray files are not packages.
+        if !path.is_dir() {
+            return Ok(());
+        }
+
+        let install_path = format!("{}node_modules/{}", prefix, name);
+        let mut package = PackageSize {
+            name: name.to_string(),
+            install_path: install_path.clone(),
+            dependencies: read_dependency_names(path),
+            ..Default::default()
+        };
+
+        if seen.folders.insert(fs::canonicalize(path)?) {
+            let mut folders = vec![path.to_path_buf()];
+            while let Some(folder) = folders.pop() {
+                for entry in fs::read_dir(&folder)?.flatten() {
+                    let metadata = entry.path().symlink_metadata()?;
+                    if !count_once(&metadata, seen) {
+                        continue;
+                    }
+                    package.bytes += disk_bytes(&metadata);
+                    if !metadata.is_dir() {
+                        package.files += 1;
+                    } else if folder != path || entry.file_name() != "node_modules" {
+                        folders.push(entry.path());
+                    }
+                }
+            }
+            self.collect(&path.join("node_modules"), &format!("{}/", install_path), seen)?;
+        }
+
+        self.packages.insert(install_path, package);
+        Ok(())
+    }
+
+    /// Returns the install paths of the package and every package it depends on.
+    fn get_subtree(&self, install_path: &str) -> HashSet<&str> {
+        let mut subtree = HashSet::new();
+        let mut queue = vec![install_path];
+
+        while let Some(path) = queue.pop() {
+            let package = match self.packages.get(path) {
+                Some(package) => package,
+                None => continue,
+            };
+            if !subtree.insert(package.install_path.as_str()) {
+                continue;
+            }
+            for name in package.dependencies.iter() {
+                let resolved = resolve_install_path(path, name, |path| self.packages.contains_key(path));
+                if let Some(resolved) = resolved {
+                    queue.push(&self.packages[&resolved].install_path);
+                }
+            }
+        }
+        subtree
+    }
+
+    /// Returns the SubtreeSize type for each of the project's dependencies that is installed.
+    pub fn get_subtrees(&self, roots: &[String]) -> Vec<SubtreeSize> {
+        let subtrees: Vec<(&PackageSize, HashSet<&str>)> = roots
+            .iter()
+            .filter_map(|name| self.packages.get(&format!("node_modules/{}", name)))
+            .map(|root| (root, self.get_subtree(&root.install_path)))
+            .collect();
+
+        let mut needed_by: HashMap<&str, usize> = HashMap::new();
+        for (_, subtree) in subtrees.iter() {
+            for path in subtree.iter() {
+                *needed_by.entry(path).or_default() += 1;
+            }
+        }
+
+        subtrees
+            .iter()
+            .map(|(root, subtree)| {
+                let bytes = |path: &&str| self.packages[*path].bytes;
+                SubtreeSize {
+                    name: root.name.clone(),
+                    bytes: root.bytes,
+                    files: root.files,
+                    packages: subtree.len(),
+                    inclusive_bytes: subtree.iter().map(bytes).sum(),
+                    exclusive_bytes: subtree
+                        .iter()
+                        .filter(|path| needed_by[*path] == 1)
+                        .map(bytes)
+                        .sum(),
+                }
+            })
+            .collect()
+    }
+}
+
+fn read_dependency_names(path: &Path) -> Vec<String> {
+    let fields = fs::read_to_string(path.join("package.json"))
+        .ok()
+        .and_then(|contents| serde_json::from_str::<DependencyFields>(&contents).ok());
+    match fields {
+        Some(fields) => fields
+            .dependencies
+            .into_keys()
+            .chain(fields.optional_dependencies.into_keys())
+            .chain(fields.peer_dependencies.into_keys())
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Returns false if the file is a hardlink to one that was already counted.
+#[cfg(unix)]
+fn count_once(metadata: &Metadata, seen: &mut Seen) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    metadata.nlink() < 2 || seen.files.insert((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn count_once(_metadata: &Metadata, _seen: &mut Seen) -> bool {
+    true
+}
+
+/// Returns the space allocated to the file, which can differ from its length.
+#[cfg(unix)]
+fn disk_bytes(metadata: &Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    metadata.blocks() * 512
+}
+
+#[cfg(not(unix))]
+fn disk_bytes(metadata: &Metadata) -> u64 {
+    metadata.len()
+}
+
+/// Returns a size such as `1.4 MB`.
+pub fn format_bytes(bytes: u64) -> String {
+    let units = ["B", "kB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1000.0 && unit < units.len() - 1 {
+        size /= 1000.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{} B", bytes),
+        _ => format!("{:.1} {}", size, units[unit]),
+    }
+}
+
+impl PrintTable for PackageSize {
+    fn table_row(&self) -> Row {
+        Row::new(vec![
+            new_cell(&self.install_path),
+            new_cell(&format_bytes(self.bytes)),
+            new_cell(&format!("{} files", self.files)),
+        ])
+    }
+}
+
+impl PrintTable for SubtreeSize {
+    fn table_row(&self) -> Row {
+        Row::new(vec![
+            new_cell(&self.name),
+            new_cell(&format!("{}, {} files", format_bytes(self.bytes), self.files)),
+            new_cell(&format!("{} exclusive", format_bytes(self.exclusive_bytes))),
+            new_cell(&format!(
+                "{} across {} packages",
+                format_bytes(self.inclusive_bytes),
+                self.packages
+            )),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::{write, TestDir};
+
+    #[test]
+    fn formats_bytes() {
+        assert_eq!(format_bytes(999), "999 B");
+        assert_eq!(format_bytes(1_400_000_000), "1.4 GB");
+    }
+
+    #[test]
+    fn measures_packages_and_subtrees() {
+        let root = TestDir::new("disk-usage");
+        let modules = root.join("node_modules");
+        write(modules.join("a/package.json"), r#"{ "dependencies": { "b": "1", "c": "1" } }"#);
+        write(modules.join("a/node_modules/b/package.json"), "{}");
+        write(modules.join("b/package.json"), r#"{ "dependencies": { "c": "1" } }"#);
+        write(modules.join("c/package.json"), "{}");
+        write(modules.join("c/lib/index.js"), &"x".repeat(10_000));
+        fs::hard_link(modules.join("c/lib/index.js"), modules.join("b/index.js")).unwrap();
+
+        let usage = DiskUsage::from(&root).unwrap();
+        let files = |path: &str| usage.packages[path].files;
+        assert_eq!(files("node_modules/a"), 1);
+        assert_eq!(files("node_modules/a/node_modules/b"), 1);
+        assert_eq!(files("node_modules/b") + files("node_modules/c"), 3);
+
+        let subtrees = usage.get_subtrees(&[String::from("a"), String::from("b")]);
+        let size = |path: &str| usage.packages[path].bytes;
+        assert_eq!(subtrees[0].packages, 3);
+        assert_eq!(
+            subtrees[0].exclusive_bytes,
+            size("node_modules/a") + size("node_modules/a/node_modules/b")
+        );
+        assert_eq!(subtrees[1].packages, 2);
+        assert_eq!(subtrees[1].exclusive_bytes, size("node_modules/b"));
+        assert_eq!(
+            subtrees[1].inclusive_bytes,
+            size("node_modules/b") + size("node_modules/c")
+        );
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+use std::process::Command;
+
+use prettytable::{color, Attr, Row};
+use serde_json::Value;
+use which::which;
+
+use crate::node_module::standard_module::StandardModule;
+use crate::node_module::{new_cell, PrintTable};
+use crate::range::Range;
+use crate::semver::Semver;
+
+/// Returns the engine ranges from the `engines` field, which is normally an object such as
+/// `{ "node": ">=18" }` but was once an array such as `["node >= 0.8"]`.
+pub fn get_engines(engines: Option<&Value>) -> HashMap<String, String> {
+    let mut ranges = HashMap::new();
+    match engines {
+        Some(Value::Object(object)) => {
+            for (engine, range) in object {
+                if let Value::String(range) = range {
+                    ranges.insert(engine.clone(), range.clone());
+                }
+            }
+        }
+        Some(Value::Array(array)) => {
+            for entry in array.iter().filter_map(Value::as_str) {
+                let entry = entry.trim();
+                let (engine, range) = match entry.find(|c: char| !c.is_ascii_alphanumeric()) {
+                    Some(i) => (&entry[..i], entry[i..].trim()),
+                    None => (entry, ""),
+                };
+                ranges.insert(engine.to_string(), range.to_string());
+            }
+        }
+        _ => {}
+    }
+    ranges
+}
+
+/// Returns the version for --target-node or --target-npm, where a bare major or major.minor such
+/// as `22` or `v22.1` stands for its first release.
+pub fn parse_target_version(target: &str) -> Option<Semver> {
+    let target = target.trim().trim_start_matches('v');
+    let is_number = |part: &str| !part.is_empty() && part.bytes().all(|byte| byte.is_ascii_digit());
+    let parts: Vec<&str> = target.split('.').collect();
+    let version = match parts.len() {
+        1 | 2 if parts.iter().all(|part| is_number(part)) => {
+            format!("{}{}", target, ".0".repeat(3 - parts.len()))
+        }
+        _ => target.to_string(),
+    };
+    Semver::from(version)
+}
+
+/// Returns the version printed by `<binary> --version` for the binary found on PATH.
+pub fn get_binary_version(binary: &str) -> Option<Semver> {
+    let path = which(binary).ok()?;
+    let output = Command::new(path).arg("--version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Semver::from(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[derive(Debug, PartialEq)]
+pub enum EngineIssue {
+    Incompatible,
+    InvalidRange,
+}
+
+/// A package whose engines range excludes the version it will run on.
+pub struct EngineFinding<'a> {
+    pub module: &'a StandardModule,
+    pub engine: String,
+    pub range: String,
+    pub target: Semver,
+    pub issue: EngineIssue,
+}
+
+impl<'a> EngineFinding<'a> {
+    /// Returns the EngineFinding type for each engine the package declares a range for that
+    /// does not include the target version.
+    pub fn from(module: &'a StandardModule, targets: &[(&str, &Semver)]) -> Vec<Self> {
+        let mut findings = Vec::new();
+        for (engine, target) in targets {
+            let range = match module.engines.get(*engine) {
+                Some(range) => range,
+                None => continue,
+            };
+            let issue = match Range::parse(range) {
+                Some(parsed) if parsed.satisfies_including_prerelease(target) => continue,
+                Some(_) => EngineIssue::Incompatible,
+                None => EngineIssue::InvalidRange,
+            };
+            findings.push(EngineFinding {
+                module,
+                engine: engine.to_string(),
+                range: range.clone(),
+                target: (*target).clone(),
+                issue,
+            });
+        }
+        findings
+    }
+}
+
+impl<'a> PrintTable for EngineFinding<'a> {
+    fn table_row(&self) -> Row {
+        let (issue, colour) = match self.issue {
+            EngineIssue::Incompatible => ("incompatible", color::RED),
+            EngineIssue::InvalidRange => ("invalid range", color::YELLOW),
+        };
+        let version = match &self.module.version {
+            Some(version) => version.to_string(),
+            None => String::new(),
+        };
+        Row::new(vec![
+            new_cell(&self.module.name),
+            new_cell(&version),
+            new_cell(&format!("{} {}", self.engine, self.range)),
+            new_cell(&format!("{} {}", self.engine, self.target)),
+            new_cell(issue).with_style(Attr::ForegroundColor(colour)),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(version: &str) -> Semver {
+        Semver::from(String::from(version)).unwrap()
+    }
+
+    #[test]
+    fn reads_engines() {
+        let object = serde_json::json!({ "node": ">=18", "npm": ">=9", "vscode": 1 });
+        let engines = get_engines(Some(&object));
+        assert_eq!(engines.len(), 2);
+        assert_eq!(engines["node"], ">=18");
+
+        let array = serde_json::json!(["node >= 0.8", "npm"]);
+        let engines = get_engines(Some(&array));
+        assert_eq!(engines["node"], ">= 0.8");
+        assert_eq!(engines["npm"], "");
+    }
+
+    #[test]
+    fn parses_target_versions() {
+        assert_eq!(parse_target_version("22").unwrap().to_string(), "22.0.0");
+        assert_eq!(parse_target_version("v22.1").unwrap().to_string(), "22.1.0");
+        assert_eq!(parse_target_version("20.11.1").unwrap().to_string(), "20.11.1");
+        assert_eq!(parse_target_version("23.0.0-rc.1").unwrap().to_string(), "23.0.0-rc.1");
+        assert!(parse_target_version("latest").is_none());
+    }
+
+    #[test]
+    fn finds_incompatible_engines() {
+        let mut module = StandardModule::default();
+        module.engines.insert(String::from("node"), String::from(">=18 <22"));
+        module.engines.insert(String::from("npm"), String::from("not a range"));
+
+        let node = version("22.1.0");
+        let npm = version("10.0.0");
+        let findings = EngineFinding::from(&module, &[("node", &node), ("npm", &npm), ("yarn", &npm)]);
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].issue, EngineIssue::Incompatible);
+        assert_eq!(findings[1].issue, EngineIssue::InvalidRange);
+
+        assert!(EngineFinding::from(&module, &[("node", &version("20.11.1"))]).is_empty());
+        assert!(EngineFinding::from(&module, &[("node", &version("20.0.0-rc.1"))]).is_empty());
+        assert_eq!(EngineFinding::from(&module, &[("node", &version("18.0.0-rc.1"))]).len(), 1);
+
+        let mut open_ended = StandardModule::default();
+        open_ended.engines.insert(String::from("node"), String::from(">=14"));
+        assert!(EngineFinding::from(&open_ended, &[("node", &version("23.0.0-rc.1"))]).is_empty());
+        assert!(EngineFinding::from(&open_ended, &[("node", &version("24.0.0-nightly20250101"))]).is_empty());
+    }
+}
@@ -0,0 +1,231 @@
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use prettytable::Row;
+use serde::Serialize;
+
+use crate::node_module::standard_module::StandardModule;
+use crate::node_module::{get_dep_type, new_cell, DepType, PrintTable};
+use crate::pjson_detail::PjsonDetail;
+use crate::range::Range;
+
+/// Returns every folder under `root` with a package.json, skipping node_modules and hidden
+/// folders such as .git.
+pub fn find_projects(root: &Path) -> Vec<PathBuf> {
+    let mut projects = Vec::new();
+    if root.join("package.json").is_file() {
+        projects.push(root.to_path_buf());
+    }
+
+    let entries = match fs::read_dir(root) {
+        Ok(entries) => entries,
+        Err(_) => return projects,
+    };
+    let mut folders: Vec<PathBuf> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name != "node_modules" && !name.starts_with('.')
+        })
+        .map(|entry| entry.path())
+        .collect();
+    folders.sort();
+
+    for folder in folders {
+        projects.extend(find_projects(&folder));
+    }
+    projects
+}
+
+/// Returns the dependencies a project declares, for projects with neither node_modules nor a
+/// lockfile.
+pub fn get_declared_deps(app_pjson: &PjsonDetail) -> Vec<StandardModule> {
+    let mut names: Vec<&String> = app_pjson
+        .dependencies
+        .iter()
+        .chain(app_pjson.dev_dependencies.iter())
+        .flat_map(|dependencies| dependencies.keys())
+        .collect();
+    names.sort();
+    names.dedup();
+
+    names
+        .into_iter()
+        .map(|name| StandardModule {
+            name: name.clone(),
+            dep_type: get_dep_type(name, app_pjson),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// A package to look for, optionally limited to the versions in a range, written like
+/// `moment` or `lodash@<4.17.21`.
+pub struct Query {
+    pub name: String,
+    pub range: Option<Range>,
+}
+
+impl Query {
+    pub fn parse(query: &str) -> Result<Self, Error> {
+        // The name's scope also starts with an @, so the range follows the last one after it.
+        let unscoped = query.strip_prefix('@').unwrap_or(query);
+        let (name, range) = match unscoped.rfind('@') {
+            Some(index) => (&query[..query.len() - unscoped.len() + index], Some(&unscoped[index + 1..])),
+            None => (query, None),
+        };
+        let range = match range {
+            Some(range) => Some(Range::parse(range).ok_or_else(|| {
+                Error::new(ErrorKind::InvalidInput, format!("{} is not a valid range in {}", range, query))
+            })?),
+            None => None,
+        };
+        Ok(Query {
+            name: name.to_string(),
+            range,
+        })
+    }
+
+    /// Returns true if the package is the one queried for, at a version in the range if there is
+    /// one.
+    pub fn matches(&self, module: &StandardModule) -> bool {
+        if module.name != self.name {
+            return false;
+        }
+        match (&self.range, &module.version) {
+            (None, _) => true,
+            (Some(range), Some(version)) => range.satisfies(version),
+            (Some(_), None) => false,
+        }
+    }
+}
+
+/// A package found in a project by `scan`.
+#[derive(Debug, Serialize)]
+pub struct FleetMatch {
+    pub project: String,
+    pub path: String,
+    pub package: String,
+    pub version: Option<String>,
+    pub dep_type: String,
+    pub declared: Option<String>,
+}
+
+impl FleetMatch {
+    pub fn from(project: &str, path: &Path, module: &StandardModule) -> Self {
+        let declared = match &module.dep_type {
+            DepType::Dependency(spec) | DepType::DevDependency(spec) => Some(spec.to_string()),
+            DepType::ChildDependency => None,
+        };
+        FleetMatch {
+            project: project.to_string(),
+            path: path.display().to_string(),
+            package: module.name.clone(),
+            version: module.version.as_ref().map(|version| version.to_string()),
+            dep_type: module.dep_type.name().to_string(),
+            declared,
+        }
+    }
+}
+
+impl PrintTable for FleetMatch {
+    fn table_row(&self) -> Row {
+        Row::new(vec![
+            new_cell(&self.package),
+            new_cell(&self.dep_type),
+            new_cell(self.declared.as_deref().unwrap_or("")),
+            new_cell(self.version.as_deref().unwrap_or("not installed")),
+        ])
+    }
+}
+
+/// Returns the matches as CSV, with a header row.
+pub fn to_csv(matches: &[FleetMatch]) -> String {
+    let mut csv = String::from("project,path,package,version,dep_type,declared\n");
+    for found in matches {
+        let fields = [
+            found.project.as_str(),
+            found.path.as_str(),
+            found.package.as_str(),
+            found.version.as_deref().unwrap_or(""),
+            found.dep_type.as_str(),
+            found.declared.as_deref().unwrap_or(""),
+        ];
+        let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn csv_field(field: &str) -> String {
+    match field.contains([',', '"', '\n']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semver::Semver;
+    use crate::test_dir::TestDir;
+
+    #[test]
+    fn finds_projects() {
+        let root = TestDir::new("fleet");
+        for folder in ["a", "a/node_modules/x", "b/packages/c", ".git/d", "e"].iter() {
+            fs::create_dir_all(root.join(folder)).unwrap();
+        }
+        for folder in ["a", "a/node_modules/x", "b/packages/c", ".git/d"].iter() {
+            fs::write(root.join(folder).join("package.json"), "{}").unwrap();
+        }
+
+        assert_eq!(find_projects(&root), vec![root.join("a"), root.join("b/packages/c")]);
+    }
+
+    #[test]
+    fn matches_queries() {
+        let module = |name: &str, version: Option<&str>| StandardModule {
+            name: name.to_string(),
+            version: version.and_then(|version| Semver::from(version.to_string())),
+            ..Default::default()
+        };
+
+        let below = Query::parse("lodash@<4.17.21").unwrap();
+        assert!(below.matches(&module("lodash", Some("4.17.15"))));
+        assert!(!below.matches(&module("lodash", Some("4.17.21"))));
+        assert!(!below.matches(&module("lodash", None)));
+        assert!(!below.matches(&module("moment", Some("2.0.0"))));
+
+        let any = Query::parse("moment").unwrap();
+        assert!(any.matches(&module("moment", None)));
+
+        let scoped = Query::parse("@types/node@^20").unwrap();
+        assert_eq!(scoped.name, "@types/node");
+        assert!(scoped.matches(&module("@types/node", Some("20.11.0"))));
+        assert_eq!(Query::parse("@types/node").unwrap().name, "@types/node");
+        assert!(Query::parse("lodash@not a range").is_err());
+        assert_eq!(Query::parse("é").unwrap().name, "é");
+        assert_eq!(Query::parse("@").unwrap().name, "@");
+    }
+
+    #[test]
+    fn writes_csv() {
+        let found = FleetMatch {
+            project: String::from("api, v2"),
+            path: String::from("/src/api"),
+            package: String::from("lodash"),
+            version: Some(String::from("4.17.15")),
+            dep_type: String::from("dependency"),
+            declared: Some(String::from("^4.17.0")),
+        };
+        assert_eq!(
+            to_csv(&[found]),
+            "project,path,package,version,dep_type,declared\n\"api, v2\",/src/api,lodash,4.17.15,dependency,^4.17.0\n"
+        );
+    }
+}
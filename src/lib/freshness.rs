@@ -0,0 +1,137 @@
+use crate::registry::Packument;
+use crate::semver::Semver;
+
+/// How far an installed version is behind the latest release.
+#[derive(Debug, PartialEq)]
+pub struct Freshness {
+    /// Days between the publish times of the installed and latest versions.
+    pub days: i64,
+    /// The number of major, minor and patch releases between the installed and latest versions.
+    pub major: usize,
+    pub minor: usize,
+    pub patch: usize,
+}
+
+impl Freshness {
+    /// Returns the Freshness type, or None if the packument is missing either publish time.
+    pub fn from(current: &Semver, packument: &Packument) -> Option<Self> {
+        let latest = packument.latest()?;
+        if *current >= latest {
+            return Some(Freshness {
+                days: 0,
+                major: 0,
+                minor: 0,
+                patch: 0,
+            });
+        }
+
+        let published = |version: &Semver| {
+            packument
+                .time
+                .get(&bare_version(version))
+                .and_then(|time| parse_days(time))
+        };
+        let days = (published(&latest)? - published(current)?).max(0);
+
+        let mut versions: Vec<Semver> = packument
+            .versions()
+            .into_iter()
+            .filter(|version| version.pre_release.is_none() && version <= &latest)
+            .collect();
+        versions.sort();
+
+        let mut freshness = Freshness {
+            days,
+            major: 0,
+            minor: 0,
+            patch: 0,
+        };
+        let mut previous = current.numbers();
+        for version in versions.iter().filter(|version| *version > current) {
+            let numbers = version.numbers();
+            if numbers.0 != previous.0 {
+                freshness.major += 1;
+            } else if numbers.1 != previous.1 {
+                freshness.minor += 1;
+            } else {
+                freshness.patch += 1;
+            }
+            previous = numbers;
+        }
+
+        Some(freshness)
+    }
+
+    pub fn libyears(&self) -> f64 {
+        self.days as f64 / 365.25
+    }
+}
+
+fn bare_version(version: &Semver) -> String {
+    match &version.pre_release {
+        Some(pre_release) => format!("{}.{}.{}-{}", version.major, version.minor, version.patch, pre_release),
+        None => format!("{}.{}.{}", version.major, version.minor, version.patch),
+    }
+}
+
+/// Returns the number of days since the Unix epoch for an ISO 8601 timestamp such as
+/// `2020-01-31T12:00:00.000Z`.
+pub fn parse_days(timestamp: &str) -> Option<i64> {
+    let date = timestamp.get(0..10)?;
+    let mut parts = date.split('-').map(|part| part.parse::<i64>());
+    let (year, month, day) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(Ok(year)), Some(Ok(month)), Some(Ok(day))) => (year, month, day),
+        _ => return None,
+    };
+
+    // Howard Hinnant's days_from_civil.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    Some(era * 146_097 + day_of_era - 719_468)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(version: &str) -> Semver {
+        Semver::from(String::from(version)).unwrap()
+    }
+
+    #[test]
+    fn parses_days() {
+        assert_eq!(parse_days("1970-01-01T00:00:00.000Z"), Some(0));
+        assert_eq!(parse_days("2000-03-01T00:00:00.000Z"), Some(11_017));
+        assert_eq!(parse_days("modified"), None);
+    }
+
+    #[test]
+    fn measures_how_far_behind_a_version_is() {
+        let packument = Packument::parse(
+            r#"{
+                "name": "a",
+                "dist-tags": { "latest": "2.1.0" },
+                "versions": {
+                    "1.0.0": {}, "1.0.1": {}, "1.1.0": {}, "2.0.0": {}, "2.1.0": {}, "3.0.0-beta.1": {}
+                },
+                "time": {
+                    "1.0.0": "2020-01-01T00:00:00.000Z",
+                    "2.1.0": "2021-01-01T00:00:00.000Z"
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let freshness = Freshness::from(&version("1.0.0"), &packument).unwrap();
+        assert_eq!(freshness.days, 366);
+        assert_eq!((freshness.major, freshness.minor, freshness.patch), (1, 2, 1));
+        assert!((freshness.libyears() - 1.002).abs() < 0.001);
+
+        assert_eq!(Freshness::from(&version("2.1.0"), &packument).unwrap().days, 0);
+        assert_eq!(Freshness::from(&version("1.0.1"), &packument), None);
+    }
+}
@@ -0,0 +1,197 @@
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+use std::process::{Command, Output};
+
+use crate::lockfile::{Lockfile, LOCKFILES};
+use crate::pjson_detail::PjsonDetail;
+
+/// Returns the revisions to compare for `--diff-rev`: `A..B` compares A with B, and a single
+/// revision is compared with the working tree, which is None. As in git, a missing side of `A..B`
+/// is HEAD.
+pub fn parse_revisions(revisions: &str) -> Result<(&str, Option<&str>), Error> {
+    if revisions.contains("...") {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("{} compares with a merge base, which is not supported; use A..B", revisions),
+        ));
+    }
+    Ok(match revisions.split_once("..") {
+        Some((from, to)) => (or_head_revision(from), Some(or_head_revision(to))),
+        None => (revisions, None),
+    })
+}
+
+fn or_head_revision(revision: &str) -> &str {
+    match revision.is_empty() {
+        true => "HEAD",
+        false => revision,
+    }
+}
+
+/// A commit from `git log`.
+#[derive(Debug, PartialEq)]
+pub struct Commit {
+    pub hash: String,
+    pub author: String,
+    /// The author date, as YYYY-MM-DD.
+    pub date: String,
+    pub subject: String,
+}
+
+/// Returns the commits on the current branch that changed any of the project's files, oldest
+/// first. Commits made on merged branches are left out, so each commit follows the one before.
+pub fn log(project: &Path, file_names: &[&str]) -> Result<Vec<Commit>, Error> {
+    let mut args = vec!["log", "--first-parent", "--reverse", "--date=short", "--format=%H%x1f%an%x1f%ad%x1f%s", "--"];
+    args.extend(file_names);
+    let output = run_git(project, &args)?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::other(format!("git log failed: {}", stderr.trim())));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(4, '\u{1f}');
+            Some(Commit {
+                hash: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                date: fields.next()?.to_string(),
+                subject: fields.next().unwrap_or_default().to_string(),
+            })
+        })
+        .collect())
+}
+
+/// Returns the contents of a file in the project as of a git revision, or None if the file did
+/// not exist then.
+pub fn show_file(project: &Path, revision: &str, file_name: &str) -> Result<Option<String>, Error> {
+    // The revision and the file are checked by exit status, as git's messages may be translated.
+    let commit = format!("{}^{{commit}}", revision);
+    if !run_git(project, &["rev-parse", "--verify", "--quiet", &commit])?.status.success() {
+        return Err(Error::new(ErrorKind::NotFound, format!("{} is not a git revision", revision)));
+    }
+    let object = format!("{}:./{}", revision, file_name);
+    if !run_git(project, &["cat-file", "-e", &object])?.status.success() {
+        return Ok(None);
+    }
+
+    let output = run_git(project, &["show", &object])?;
+    match output.status.success() {
+        true => Ok(Some(String::from_utf8_lossy(&output.stdout).to_string())),
+        false => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(Error::other(format!("git show {}:{} failed: {}", revision, file_name, stderr.trim())))
+        }
+    }
+}
+
+fn run_git(project: &Path, args: &[&str]) -> Result<Output, Error> {
+    Command::new("git")
+        .arg("-C")
+        .arg(project)
+        .args(args)
+        .output()
+        .map_err(|err| Error::new(err.kind(), format!("failed to run git: {}", err)))
+}
+
+/// Returns the project's package.json and lockfile as of a git revision.
+pub fn read_revision(project: &Path, revision: &str) -> Result<(PjsonDetail, Lockfile), Error> {
+    let app_pjson = show_file(project, revision, "package.json")?.ok_or_else(|| {
+        Error::new(ErrorKind::NotFound, format!("there is no package.json at {}", revision))
+    })?;
+    let app_pjson = PjsonDetail::parse(&app_pjson)?;
+
+    match read_lockfile(project, revision)? {
+        Some(lockfile) => Ok((app_pjson, lockfile)),
+        None => Err(Error::new(
+            ErrorKind::NotFound,
+            format!("there is no package-lock.json or npm-shrinkwrap.json at {}", revision),
+        )),
+    }
+}
+
+/// Returns the project's lockfile as of a git revision, if it had one.
+pub fn read_lockfile(project: &Path, revision: &str) -> Result<Option<Lockfile>, Error> {
+    for file_name in LOCKFILES.iter() {
+        if let Some(lockfile) = show_file(project, revision, file_name)? {
+            return Ok(Some(Lockfile::parse(&lockfile)?));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_module::diffed_pair::DiffedPair;
+    use crate::node_module::get_locked_deps;
+    use crate::test_dir::TestDir;
+    use std::fs;
+
+    fn git(repo: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(repo)
+            .args(["-c", "user.name=nodever", "-c", "user.email=nodever@example.com"])
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    fn commit(repo: &Path, left_pad: &str, message: &str) {
+        fs::write(
+            repo.join("package.json"),
+            format!(r#"{{ "name": "app", "version": "1.0.0", "dependencies": {{ "left-pad": "^{}" }} }}"#, left_pad),
+        )
+        .unwrap();
+        fs::write(
+            repo.join("package-lock.json"),
+            format!(
+                r#"{{ "lockfileVersion": 3, "packages": {{ "": {{}}, "node_modules/left-pad": {{ "version": "{}" }} }} }}"#,
+                left_pad
+            ),
+        )
+        .unwrap();
+        git(repo, &["add", "-A"]);
+        git(repo, &["commit", "-q", "-m", message]);
+    }
+
+    #[test]
+    fn reads_revisions() {
+        assert_eq!(parse_revisions("main").unwrap(), ("main", None));
+        assert_eq!(parse_revisions("v1..HEAD").unwrap(), ("v1", Some("HEAD")));
+        assert_eq!(parse_revisions("..v2").unwrap(), ("HEAD", Some("v2")));
+        assert_eq!(parse_revisions("v1..").unwrap(), ("v1", Some("HEAD")));
+        assert!(parse_revisions("v1...v2").is_err());
+
+        let repo = TestDir::new("git");
+        git(&repo, &["init", "-q"]);
+        commit(&repo, "1.1.0", "first");
+        commit(&repo, "1.3.0", "second");
+
+        let (old_pjson, old_lockfile) = read_revision(&repo, "HEAD~1").unwrap();
+        let (new_pjson, new_lockfile) = read_revision(&repo, "HEAD").unwrap();
+        let old = get_locked_deps(Some(&old_pjson), &old_lockfile);
+        let new = get_locked_deps(Some(&new_pjson), &new_lockfile);
+        let pairs = DiffedPair::get_pairs(&old, &new);
+        assert_eq!(pairs.len(), 1);
+        assert_eq!(pairs[0].version.0.as_ref().unwrap().to_string(), "1.1.0");
+        assert_eq!(pairs[0].version.1.as_ref().unwrap().to_string(), "1.3.0");
+
+        assert!(show_file(&repo, "HEAD", "npm-shrinkwrap.json").unwrap().is_none());
+        let subjects = || -> Vec<String> {
+            log(&repo, &["package.json"]).unwrap().into_iter().map(|commit| commit.subject).collect()
+        };
+        assert_eq!(subjects(), vec!["first", "second"]);
+
+        git(&repo, &["checkout", "-q", "-b", "feature"]);
+        commit(&repo, "1.3.1", "on a branch");
+        git(&repo, &["checkout", "-q", "-"]);
+        git(&repo, &["merge", "-q", "--no-ff", "-m", "merge", "feature"]);
+        assert_eq!(subjects(), vec!["first", "second", "merge"]);
+        assert!(read_revision(&repo, "no-such-branch").is_err());
+    }
+}
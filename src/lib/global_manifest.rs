@@ -0,0 +1,238 @@
+use std::cmp::Ordering;
+use std::fs;
+use std::io::Error;
+use std::path::Path;
+
+use prettytable::{color, Attr, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::global_prefix::GlobalManager;
+use crate::node_module::global_module::GlobalModule;
+use crate::node_module::{new_cell, PrintTable};
+use crate::semver::Semver;
+use crate::source::Source;
+
+/// A global package recorded in a manifest.
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct ManifestPackage {
+    pub name: String,
+    pub version: String,
+    /// What to pass to the package manager to install the package again, e.g.
+    /// `typescript@5.4.2`, a git URL or the folder a linked package points to.
+    pub spec: String,
+}
+
+impl ManifestPackage {
+    pub fn from(module: &GlobalModule) -> Self {
+        let spec = match Source::from(module.resolved.as_deref(), module.link.as_ref()) {
+            Source::Local(path) => path,
+            Source::Git { .. } | Source::Tarball(_) => module.resolved.clone().unwrap_or_default(),
+            _ => format!("{}@{}", module.name, module.version),
+        };
+        ManifestPackage {
+            name: module.name.clone(),
+            version: module.version.clone(),
+            spec,
+        }
+    }
+}
+
+/// The global packages of a Node installation, written by `--global --export`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct GlobalManifest {
+    pub manager: String,
+    pub packages: Vec<ManifestPackage>,
+}
+
+impl GlobalManifest {
+    pub fn from(manager: &str, modules: &[GlobalModule]) -> Self {
+        GlobalManifest {
+            manager: manager.to_string(),
+            packages: modules.iter().map(ManifestPackage::from).collect(),
+        }
+    }
+
+    /// Returns the GlobalManifest type from a file written by `write`.
+    pub fn read(path: &Path) -> Result<Self, Error> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), Error> {
+        fs::write(path, serde_json::to_string_pretty(self)? + "\n")
+    }
+
+    /// Returns what needs to happen to each package in the manifest for the installed global
+    /// packages to match it.
+    pub fn plan<'a>(&'a self, installed: &'a [GlobalModule]) -> Vec<PlanStep<'a>> {
+        self.packages
+            .iter()
+            .map(|package| {
+                let current = installed.iter().find(|module| module.name == package.name);
+                let action = match current {
+                    None => PlanAction::Install,
+                    Some(current) if current.version == package.version => PlanAction::Keep,
+                    Some(current) => {
+                        let wanted = Semver::from(package.version.clone());
+                        let current = Semver::from(current.version.clone());
+                        match (wanted, current) {
+                            (Some(wanted), Some(current)) => match wanted.cmp(&current) {
+                                Ordering::Greater => PlanAction::Upgrade,
+                                Ordering::Less => PlanAction::Downgrade,
+                                Ordering::Equal => PlanAction::Keep,
+                            },
+                            _ => PlanAction::Replace,
+                        }
+                    }
+                };
+                PlanStep {
+                    package,
+                    current,
+                    action,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the installed global packages the manifest does not list.
+    pub fn unlisted<'a>(&self, installed: &'a [GlobalModule]) -> Vec<&'a GlobalModule> {
+        installed
+            .iter()
+            .filter(|module| !self.packages.iter().any(|package| package.name == module.name))
+            .collect()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PlanAction {
+    Install,
+    Upgrade,
+    Downgrade,
+    /// The installed and manifest versions can't be compared, e.g. for git installs.
+    Replace,
+    Keep,
+}
+
+/// A package in a manifest and the installed global package of the same name.
+pub struct PlanStep<'a> {
+    pub package: &'a ManifestPackage,
+    pub current: Option<&'a GlobalModule>,
+    pub action: PlanAction,
+}
+
+impl<'a> PlanStep<'a> {
+    /// Returns the command that reproduces the manifest's copy of the package, unless it is
+    /// already installed.
+    pub fn command(&self, manager: &GlobalManager) -> Option<String> {
+        match self.action {
+            PlanAction::Keep => None,
+            _ => Some(format!("{} {}", manager.install_command(), shell_quote(&self.package.spec))),
+        }
+    }
+}
+
+impl<'a> PrintTable for PlanStep<'a> {
+    fn table_row(&self) -> Row {
+        let action = match self.action {
+            PlanAction::Install => new_cell("install").with_style(Attr::ForegroundColor(color::GREEN)),
+            PlanAction::Upgrade => new_cell("upgrade").with_style(Attr::ForegroundColor(color::YELLOW)),
+            PlanAction::Downgrade => new_cell("downgrade").with_style(Attr::ForegroundColor(color::YELLOW)),
+            PlanAction::Replace => new_cell("replace").with_style(Attr::ForegroundColor(color::YELLOW)),
+            PlanAction::Keep => new_cell("installed"),
+        };
+        let current = self.current.map_or("", |current| current.version.as_str());
+        Row::new(vec![
+            new_cell(&self.package.name),
+            new_cell(current),
+            new_cell(&self.package.version),
+            action,
+        ])
+    }
+}
+
+/// Returns the shell script that runs the commands in order, stopping at the first failure.
+pub fn to_script(commands: &[String]) -> String {
+    let mut script = String::from("#!/bin/sh\nset -e\n\n");
+    for command in commands {
+        script.push_str(command);
+        script.push('\n');
+    }
+    script
+}
+
+/// Quotes an argument for a POSIX shell when it contains anything other than characters that
+/// appear in package specs.
+fn shell_quote(argument: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "@/._-:+=~^#".contains(c);
+    match !argument.is_empty() && argument.chars().all(plain) {
+        true => argument.to_string(),
+        false => format!("'{}'", argument.replace('\'', r"'\''")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::link::Link;
+    use std::path::PathBuf;
+
+    fn module(name: &str, version: &str) -> GlobalModule {
+        GlobalModule {
+            name: name.to_string(),
+            version: version.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn records_install_specs() {
+        let registry = module("typescript", "5.4.2");
+        let git = GlobalModule {
+            resolved: Some(String::from("git+ssh://git@github.com/me/tool.git#abc123")),
+            ..module("tool", "1.0.0")
+        };
+        let linked = GlobalModule {
+            link: Some(Link {
+                target: PathBuf::from("/src/my tool"),
+                in_repo: false,
+                dangling: false,
+            }),
+            ..module("my-tool", "0.1.0")
+        };
+
+        let manifest = GlobalManifest::from("npm", &[registry, git, linked]);
+        let specs: Vec<&str> = manifest.packages.iter().map(|package| package.spec.as_str()).collect();
+        assert_eq!(
+            specs,
+            vec!["typescript@5.4.2", "git+ssh://git@github.com/me/tool.git#abc123", "/src/my tool"]
+        );
+    }
+
+    #[test]
+    fn plans_install_commands() {
+        let manifest = GlobalManifest::from(
+            "npm",
+            &[module("a", "1.0.0"), module("b", "2.0.0"), module("c", "1.0.0"), module("d", "1.0.0")],
+        );
+        let installed = vec![module("b", "1.5.0"), module("c", "1.0.0"), module("d", "1.2.0"), module("e", "1.0.0")];
+
+        let plan = manifest.plan(&installed);
+        let actions: Vec<&PlanAction> = plan.iter().map(|step| &step.action).collect();
+        assert_eq!(
+            actions,
+            vec![&PlanAction::Install, &PlanAction::Upgrade, &PlanAction::Keep, &PlanAction::Downgrade]
+        );
+
+        let commands: Vec<String> = plan.iter().filter_map(|step| step.command(&GlobalManager::Pnpm)).collect();
+        assert_eq!(commands, vec!["pnpm add -g a@1.0.0", "pnpm add -g b@2.0.0", "pnpm add -g d@1.0.0"]);
+
+        let unlisted: Vec<&str> = manifest.unlisted(&installed).iter().map(|module| module.name.as_str()).collect();
+        assert_eq!(unlisted, vec!["e"]);
+    }
+
+    #[test]
+    fn quotes_shell_arguments() {
+        assert_eq!(shell_quote("@scope/a@^1.0.0"), "@scope/a@^1.0.0");
+        assert_eq!(shell_quote("/src/my tool"), "'/src/my tool'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+    }
+}
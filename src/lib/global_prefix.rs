@@ -0,0 +1,304 @@
+use std::env;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use which::which;
+
+use crate::installations::Installation;
+use crate::npmrc::Npmrc;
+
+/// The package manager whose global packages to list.
+#[derive(Debug, PartialEq)]
+pub enum GlobalManager {
+    Npm,
+    Yarn,
+    Pnpm,
+}
+
+impl GlobalManager {
+    pub fn from(name: &str) -> Option<Self> {
+        match name {
+            "npm" => Some(GlobalManager::Npm),
+            "yarn" => Some(GlobalManager::Yarn),
+            "pnpm" => Some(GlobalManager::Pnpm),
+            _ => None,
+        }
+    }
+
+    /// Returns the command that installs packages globally, without the packages.
+    pub fn install_command(&self) -> &'static str {
+        match self {
+            GlobalManager::Npm => "npm install -g",
+            GlobalManager::Yarn => "yarn global add",
+            GlobalManager::Pnpm => "pnpm add -g",
+        }
+    }
+}
+
+/// Where a package manager installs global packages and links their executables.
+#[derive(Debug)]
+pub struct GlobalRoot {
+    pub node_modules: PathBuf,
+    pub bin: PathBuf,
+}
+
+/// Returns the GlobalRoot type for the package manager.
+pub fn get_global_root(manager: &GlobalManager) -> Result<GlobalRoot, Error> {
+    let home = PathBuf::from(env::var("HOME").unwrap_or_default());
+    let from_env = |name: &str| env::var_os(name).map(PathBuf::from);
+    let node_prefix = which("node").ok().and_then(|node| get_node_prefix(&node));
+
+    let (name, node_modules, bin) = match manager {
+        GlobalManager::Npm => {
+            let npmrc = Npmrc::global(node_prefix.as_deref())?;
+            let prefix = get_npm_prefix(&npmrc, from_env("PREFIX"), node_prefix, &home).ok_or_else(|| {
+                Error::new(
+                    ErrorKind::NotFound,
+                    "could not find node on PATH to locate the global prefix; set prefix in .npmrc or NPM_CONFIG_PREFIX",
+                )
+            })?;
+            ("npm", get_npm_node_modules(&prefix), get_npm_bin(&prefix))
+        }
+        GlobalManager::Yarn => {
+            let yarnrc = fs::read_to_string(home.join(".yarnrc")).unwrap_or_default();
+            let global_folder = from_env("YARN_GLOBAL_FOLDER")
+                .or_else(|| read_yarnrc(&yarnrc, "global-folder").map(|folder| expand_home(&folder, &home)))
+                .unwrap_or_else(|| get_yarn_data_dir(from_env("XDG_DATA_HOME"), &home).join("global"));
+            let bin = from_env("YARN_PREFIX")
+                .or_else(|| read_yarnrc(&yarnrc, "prefix").map(|prefix| expand_home(&prefix, &home)))
+                .map_or_else(|| home.join(".yarn").join("bin"), |prefix| prefix.join("bin"));
+            ("yarn", global_folder.join("node_modules"), bin)
+        }
+        GlobalManager::Pnpm => {
+            let npmrc = Npmrc::global(node_prefix.as_deref())?;
+            let pnpm_home = from_env("PNPM_HOME").unwrap_or_else(|| get_pnpm_home(from_env("XDG_DATA_HOME"), &home));
+            let global_dir = match npmrc.get("global-dir") {
+                Some(global_dir) => expand_home(global_dir, &home),
+                None => pnpm_home.join("global"),
+            };
+            let bin = match npmrc.get("global-bin-dir") {
+                Some(global_bin_dir) => expand_home(global_bin_dir, &home),
+                None => pnpm_home,
+            };
+            let node_modules = get_pnpm_node_modules(&global_dir).ok_or_else(|| {
+                Error::new(
+                    ErrorKind::NotFound,
+                    format!("no pnpm global packages were found in {}", global_dir.display()),
+                )
+            })?;
+            ("pnpm", node_modules, bin)
+        }
+    };
+
+    if !node_modules.is_dir() {
+        return Err(Error::new(
+            ErrorKind::NotFound,
+            format!("the {} global node_modules folder {} does not exist", name, node_modules.display()),
+        ));
+    }
+    Ok(GlobalRoot { node_modules, bin })
+}
+
+/// Returns the global node_modules folder to compare with `--diff`: one in an npm prefix, a
+/// node_modules folder or a folder containing one, or the newest Node installation matching a version such as `18` or
+/// `nvm:18.19.0`.
+pub fn get_diff_root(target: &Path, installations: &[Installation]) -> Result<PathBuf, Error> {
+    if target.is_dir() {
+        let candidates = [get_npm_node_modules(target), target.join("node_modules")];
+        if let Some(node_modules) = candidates.iter().find(|candidate| candidate.is_dir()) {
+            return Ok(node_modules.clone());
+        }
+        return match target.file_name().is_some_and(|name| name == "node_modules") {
+            true => Ok(target.to_path_buf()),
+            false => Err(Error::new(
+                ErrorKind::NotFound,
+                format!("{} has no node_modules folder", target.display()),
+            )),
+        };
+    }
+
+    let spec = target.to_string_lossy();
+    installations
+        .iter()
+        .filter(|installation| installation.matches(&spec))
+        .max_by(|a, b| a.version.cmp(&b.version))
+        .map(Installation::node_modules)
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                format!("{} is not a folder or the version of a Node installation", spec),
+            )
+        })
+}
+
+/// Returns the prefix Node was installed to from the path of the node binary, which is in
+/// `<prefix>/bin` except on Windows.
+pub fn get_node_prefix(node: &Path) -> Option<PathBuf> {
+    let bin = node.parent()?;
+    match cfg!(windows) {
+        true => Some(bin.to_path_buf()),
+        false => bin.parent().map(Path::to_path_buf),
+    }
+}
+
+/// Returns npm's global prefix: the `prefix` setting, else the `PREFIX` environment variable,
+/// else the prefix Node was installed to.
+fn get_npm_prefix(
+    npmrc: &Npmrc,
+    env_prefix: Option<PathBuf>,
+    node_prefix: Option<PathBuf>,
+    home: &Path,
+) -> Option<PathBuf> {
+    npmrc
+        .get("prefix")
+        .map(|prefix| expand_home(prefix, home))
+        .or(env_prefix)
+        .or(node_prefix)
+}
+
+fn get_npm_node_modules(prefix: &Path) -> PathBuf {
+    match cfg!(windows) {
+        true => prefix.join("node_modules"),
+        false => prefix.join("lib").join("node_modules"),
+    }
+}
+
+fn get_npm_bin(prefix: &Path) -> PathBuf {
+    match cfg!(windows) {
+        true => prefix.to_path_buf(),
+        false => prefix.join("bin"),
+    }
+}
+
+/// Returns the value of a setting in a Yarn classic `.yarnrc`, where lines look like
+/// `global-folder "/opt/yarn"`.
+fn read_yarnrc(contents: &str, key: &str) -> Option<String> {
+    contents.lines().find_map(|line| {
+        let line = line.trim();
+        let value = line.strip_prefix(key)?;
+        if !value.starts_with(char::is_whitespace) {
+            return None;
+        }
+        Some(value.trim().trim_matches('"').to_string())
+    })
+}
+
+fn get_yarn_data_dir(xdg_data_home: Option<PathBuf>, home: &Path) -> PathBuf {
+    if cfg!(windows) {
+        if let Some(local_app_data) = env::var_os("LOCALAPPDATA") {
+            return PathBuf::from(local_app_data).join("Yarn").join("Data");
+        }
+    }
+    match xdg_data_home {
+        Some(data_home) => data_home.join("yarn"),
+        None => home.join(".config").join("yarn"),
+    }
+}
+
+fn get_pnpm_home(xdg_data_home: Option<PathBuf>, home: &Path) -> PathBuf {
+    if cfg!(windows) {
+        if let Some(local_app_data) = env::var_os("LOCALAPPDATA") {
+            return PathBuf::from(local_app_data).join("pnpm");
+        }
+    }
+    if cfg!(target_os = "macos") {
+        return home.join("Library").join("pnpm");
+    }
+    xdg_data_home
+        .unwrap_or_else(|| home.join(".local").join("share"))
+        .join("pnpm")
+}
+
+/// Returns the node_modules folder for the newest layout version in pnpm's global folder, such
+/// as `<global-dir>/5/node_modules`.
+fn get_pnpm_node_modules(global_dir: &Path) -> Option<PathBuf> {
+    fs::read_dir(global_dir)
+        .ok()?
+        .flatten()
+        .filter_map(|entry| {
+            let layout = entry.file_name().to_str()?.parse::<u32>().ok()?;
+            Some((layout, entry.path().join("node_modules")))
+        })
+        .filter(|(_, node_modules)| node_modules.is_dir())
+        .max_by_key(|(layout, _)| *layout)
+        .map(|(_, node_modules)| node_modules)
+}
+
+fn expand_home(path: &str, home: &Path) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => home.join(rest),
+        None => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semver::Semver;
+    use crate::test_dir::TestDir;
+
+    #[test]
+    fn resolves_the_npm_prefix() {
+        let home = PathBuf::from("/home/me");
+        let node_prefix = get_node_prefix(Path::new("/opt/homebrew/bin/node"));
+        assert_eq!(node_prefix, Some(PathBuf::from("/opt/homebrew")));
+
+        let prefix = |npmrc: &str, env_prefix: Option<&str>| {
+            get_npm_prefix(&Npmrc::parse(npmrc), env_prefix.map(PathBuf::from), node_prefix.clone(), &home)
+        };
+        assert_eq!(prefix("prefix=~/.npm-global", Some("/usr")), Some(PathBuf::from("/home/me/.npm-global")));
+        assert_eq!(prefix("", Some("/usr")), Some(PathBuf::from("/usr")));
+        assert_eq!(prefix("", None), Some(PathBuf::from("/opt/homebrew")));
+        assert_eq!(get_npm_prefix(&Npmrc::default(), None, None, &home), None);
+    }
+
+    #[test]
+    fn reads_yarnrc() {
+        let yarnrc = "# comment\nglobal-folder-other x\nglobal-folder \"/opt/yarn\"\n";
+        assert_eq!(read_yarnrc(yarnrc, "global-folder").as_deref(), Some("/opt/yarn"));
+        assert_eq!(read_yarnrc(yarnrc, "prefix"), None);
+    }
+
+    #[test]
+    fn finds_the_pnpm_layout() {
+        let root = TestDir::new("pnpm-global");
+        fs::create_dir_all(root.join("4/node_modules")).unwrap();
+        fs::create_dir_all(root.join("5/node_modules")).unwrap();
+        fs::create_dir_all(root.join("6")).unwrap();
+
+        assert_eq!(get_pnpm_node_modules(&root), Some(root.join("5/node_modules")));
+        assert_eq!(get_pnpm_node_modules(&root.join("missing")), None);
+    }
+
+    #[test]
+    fn finds_diff_roots() {
+        let root = TestDir::new("diff-root");
+        let prefix = root.join("prefix");
+        let project = root.join("project");
+        fs::create_dir_all(get_npm_node_modules(&prefix)).unwrap();
+        fs::create_dir_all(project.join("node_modules")).unwrap();
+        fs::create_dir_all(root.join("nvm")).unwrap();
+        let installations = vec![
+            Installation {
+                manager: String::from("nvm"),
+                version: Semver::from(String::from("18.17.0")),
+                prefix: root.join("nvm/v18.17.0"),
+            },
+            Installation {
+                manager: String::from("asdf"),
+                version: Semver::from(String::from("18.19.0")),
+                prefix: root.join("asdf/18.19.0"),
+            },
+        ];
+
+        let diff_root = |target: &Path| get_diff_root(target, &installations).ok();
+        assert_eq!(diff_root(&prefix), Some(get_npm_node_modules(&prefix)));
+        assert_eq!(diff_root(&project), Some(project.join("node_modules")));
+        assert_eq!(diff_root(&project.join("node_modules")), Some(project.join("node_modules")));
+        assert_eq!(diff_root(&root.join("nvm")), None);
+        assert_eq!(diff_root(Path::new("18")), Some(root.join("asdf/18.19.0/lib/node_modules")));
+        assert_eq!(diff_root(Path::new("nvm:18")), Some(root.join("nvm/v18.17.0/lib/node_modules")));
+        assert_eq!(diff_root(Path::new("16")), None);
+    }
+}
@@ -0,0 +1,130 @@
+use prettytable::{color, Attr, Row};
+
+use crate::git::Commit;
+use crate::lockfile::Lockfile;
+use crate::node_module::{new_cell, PrintTable};
+use crate::pjson_detail::PjsonDetail;
+
+/// How a package is declared and locked at one revision.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PackageState {
+    pub declared: Option<String>,
+    pub version: Option<String>,
+}
+
+impl PackageState {
+    /// Returns the PackageState type for the package, from whichever of the package.json and
+    /// lockfile the revision has.
+    pub fn from(name: &str, app_pjson: Option<&PjsonDetail>, lockfile: Option<&Lockfile>) -> Self {
+        let declared = app_pjson.and_then(|app_pjson| {
+            [&app_pjson.dependencies, &app_pjson.dev_dependencies]
+                .iter()
+                .find_map(|dependencies| dependencies.as_ref()?.get(name).cloned())
+        });
+        let version = lockfile
+            .and_then(|lockfile| lockfile.get(name))
+            .and_then(|locked| locked.version.clone());
+        PackageState { declared, version }
+    }
+}
+
+/// A commit that changed how a package is declared or locked.
+pub struct HistoryEntry<'a> {
+    pub commit: &'a Commit,
+    pub old: PackageState,
+    pub new: PackageState,
+}
+
+/// Returns an entry for each commit whose state differs from the one before, given the state at
+/// each commit, oldest first.
+pub fn get_history(states: &[(Commit, PackageState)]) -> Vec<HistoryEntry<'_>> {
+    let mut history = Vec::new();
+    let mut previous = PackageState::default();
+    for (commit, state) in states {
+        if *state != previous {
+            history.push(HistoryEntry {
+                commit,
+                old: previous,
+                new: state.clone(),
+            });
+        }
+        previous = state.clone();
+    }
+    history
+}
+
+fn change(old: &Option<String>, new: &Option<String>) -> String {
+    match (old, new) {
+        (Some(old), Some(new)) if old == new => new.clone(),
+        (Some(old), Some(new)) => format!("{} → {}", old, new),
+        (None, Some(new)) => format!("added {}", new),
+        (Some(old), None) => format!("removed {}", old),
+        (None, None) => String::new(),
+    }
+}
+
+impl<'a> PrintTable for HistoryEntry<'a> {
+    fn table_row(&self) -> Row {
+        let version = change(&self.old.version, &self.new.version);
+        let version = match self.old.version != self.new.version {
+            true => new_cell(&version).with_style(Attr::ForegroundColor(color::YELLOW)),
+            false => new_cell(&version),
+        };
+        Row::new(vec![
+            new_cell(&self.commit.hash[..self.commit.hash.len().min(8)]),
+            new_cell(&self.commit.date),
+            new_cell(&self.commit.author),
+            new_cell(&change(&self.old.declared, &self.new.declared)),
+            version,
+            new_cell(&self.commit.subject),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(hash: &str) -> Commit {
+        Commit {
+            hash: hash.to_string(),
+            author: String::from("me"),
+            date: String::from("2024-01-01"),
+            subject: String::new(),
+        }
+    }
+
+    fn state(declared: Option<&str>, version: Option<&str>) -> PackageState {
+        PackageState {
+            declared: declared.map(String::from),
+            version: version.map(String::from),
+        }
+    }
+
+    #[test]
+    fn finds_changes() {
+        let app_pjson: PjsonDetail = serde_json::from_str(
+            r#"{ "name": "app", "version": "1.0.0", "devDependencies": { "a": "^1.0.0" } }"#,
+        )
+        .unwrap();
+        let lockfile = Lockfile::parse(r#"{ "packages": { "node_modules/a": { "version": "1.2.0" } } }"#).unwrap();
+        assert_eq!(
+            PackageState::from("a", Some(&app_pjson), Some(&lockfile)),
+            state(Some("^1.0.0"), Some("1.2.0"))
+        );
+        assert_eq!(PackageState::from("a", Some(&app_pjson), None), state(Some("^1.0.0"), None));
+
+        let states = vec![
+            (commit("1"), state(None, None)),
+            (commit("2"), state(Some("^1.0.0"), Some("1.0.0"))),
+            (commit("3"), state(Some("^1.0.0"), Some("1.0.0"))),
+            (commit("4"), state(Some("^1.0.0"), Some("1.2.0"))),
+            (commit("5"), state(None, None)),
+        ];
+        let history = get_history(&states);
+        let hashes: Vec<&str> = history.iter().map(|entry| entry.commit.hash.as_str()).collect();
+        assert_eq!(hashes, vec!["2", "4", "5"]);
+        assert_eq!(change(&history[1].old.version, &history[1].new.version), "1.0.0 → 1.2.0");
+        assert_eq!(change(&history[2].old.declared, &history[2].new.declared), "removed ^1.0.0");
+    }
+}
@@ -0,0 +1,182 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::semver::Semver;
+
+/// A version manager's folder of Node installations, and where each installation's prefix sits
+/// inside its version folder.
+struct Layout {
+    manager: &'static str,
+    roots: Vec<PathBuf>,
+    versions: &'static str,
+    prefix: &'static str,
+}
+
+/// A Node installation managed by nvm, fnm, Volta or asdf.
+#[derive(Debug, PartialEq)]
+pub struct Installation {
+    pub manager: String,
+    pub version: Option<Semver>,
+    /// The folder containing `bin/node` and `lib/node_modules`.
+    pub prefix: PathBuf,
+}
+
+impl Installation {
+    /// Returns every installation in the version managers' default folders, or the folders set
+    /// by `NVM_DIR`, `FNM_DIR`, `VOLTA_HOME` and `ASDF_DATA_DIR`.
+    pub fn find_all() -> Vec<Self> {
+        let home = PathBuf::from(env::var("HOME").unwrap_or_default());
+        let from_env = |name: &str| env::var_os(name).map(PathBuf::from);
+
+        let mut fnm_roots = Vec::new();
+        match from_env("FNM_DIR") {
+            Some(root) => fnm_roots.push(root),
+            None => {
+                let data_home = from_env("XDG_DATA_HOME").unwrap_or_else(|| home.join(".local/share"));
+                fnm_roots.push(data_home.join("fnm"));
+                fnm_roots.push(home.join(".fnm"));
+                fnm_roots.push(home.join("Library/Application Support/fnm"));
+            }
+        }
+
+        let layouts = [
+            Layout {
+                manager: "nvm",
+                roots: vec![from_env("NVM_DIR").unwrap_or_else(|| home.join(".nvm"))],
+                versions: "versions/node",
+                prefix: "",
+            },
+            Layout {
+                manager: "fnm",
+                roots: fnm_roots,
+                versions: "node-versions",
+                prefix: "installation",
+            },
+            Layout {
+                manager: "volta",
+                roots: vec![from_env("VOLTA_HOME").unwrap_or_else(|| home.join(".volta"))],
+                versions: "tools/image/node",
+                prefix: "",
+            },
+            Layout {
+                manager: "asdf",
+                roots: vec![from_env("ASDF_DATA_DIR").unwrap_or_else(|| home.join(".asdf"))],
+                versions: "installs/nodejs",
+                prefix: "",
+            },
+        ];
+
+        layouts.iter().flat_map(find).collect()
+    }
+
+    /// Returns the global node_modules folder of the installation.
+    pub fn node_modules(&self) -> PathBuf {
+        self.prefix.join("lib").join("node_modules")
+    }
+
+    /// Returns true if the installation matches a version such as `18`, `v18.19` or
+    /// `nvm:18.19.0`, where the version manager is optional.
+    pub fn matches(&self, spec: &str) -> bool {
+        let (manager, version) = match spec.split_once(':') {
+            Some((manager, version)) => (Some(manager), version),
+            None => (None, spec),
+        };
+        if manager.is_some_and(|manager| manager != self.manager) {
+            return false;
+        }
+        let installed = match &self.version {
+            Some(installed) => installed.to_string(),
+            None => return false,
+        };
+        let wanted: Vec<&str> = version.trim_start_matches('v').split('.').collect();
+        let installed: Vec<&str> = installed.split('.').collect();
+        !version.is_empty() && wanted.len() <= installed.len() && wanted.iter().zip(installed.iter()).all(|(a, b)| a == b)
+    }
+
+    /// Returns true if the installation is the one that provides the node binary at `path`.
+    pub fn provides(&self, path: &Path) -> bool {
+        let node = self.prefix.join("bin").join("node");
+        match (fs::canonicalize(node), fs::canonicalize(path)) {
+            (Ok(node), Ok(path)) => node == path,
+            _ => false,
+        }
+    }
+}
+
+fn find(layout: &Layout) -> Vec<Installation> {
+    let mut installations: Vec<Installation> = Vec::new();
+
+    for root in layout.roots.iter() {
+        let entries = match fs::read_dir(root.join(layout.versions)) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            let prefix = match layout.prefix.is_empty() {
+                true => entry.path(),
+                false => entry.path().join(layout.prefix),
+            };
+            if !prefix.is_dir() {
+                continue;
+            }
+            let folder_name = entry.file_name().to_string_lossy().to_string();
+            installations.push(Installation {
+                manager: layout.manager.to_string(),
+                version: Semver::from(folder_name),
+                prefix,
+            });
+        }
+    }
+
+    installations.sort_by(|a, b| a.version.cmp(&b.version).then(a.prefix.cmp(&b.prefix)));
+    installations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    #[test]
+    fn finds_installations() {
+        let root = TestDir::new("installations");
+        for version in ["v20.11.1", "v18.17.0"].iter() {
+            fs::create_dir_all(root.join("node-versions").join(version).join("installation/bin")).unwrap();
+        }
+        fs::create_dir_all(root.join("node-versions/.downloads")).unwrap();
+
+        let layout = Layout {
+            manager: "fnm",
+            roots: vec![root.to_path_buf(), root.join("missing")],
+            versions: "node-versions",
+            prefix: "installation",
+        };
+        let installations = find(&layout);
+        let versions: Vec<String> = installations
+            .iter()
+            .map(|installation| installation.version.as_ref().unwrap().to_string())
+            .collect();
+        assert_eq!(versions, vec!["18.17.0", "20.11.1"]);
+        assert_eq!(
+            installations[0].node_modules(),
+            root.join("node-versions/v18.17.0/installation/lib/node_modules")
+        );
+    }
+
+    #[test]
+    fn matches_versions() {
+        let installation = Installation {
+            manager: String::from("nvm"),
+            version: Semver::from(String::from("18.19.0")),
+            prefix: PathBuf::from("/home/me/.nvm/versions/node/v18.19.0"),
+        };
+        assert!(installation.matches("18"));
+        assert!(installation.matches("v18.19"));
+        assert!(installation.matches("nvm:18.19.0"));
+        assert!(!installation.matches("fnm:18"));
+        assert!(!installation.matches("18.1"));
+        assert!(!installation.matches("18.19.0.1"));
+        assert!(!installation.matches("nvm:"));
+    }
+}
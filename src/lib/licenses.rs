@@ -0,0 +1,387 @@
+use std::fs;
+use std::io::Error;
+use std::path::Path;
+
+use prettytable::{color, Attr, Row};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::node_module::standard_module::StandardModule;
+use crate::node_module::{new_cell, PrintTable};
+
+/// Common SPDX identifiers. Anything else is reported as unknown so it can be checked by hand.
+const KNOWN_LICENSES: [&str; 62] = [
+    "0BSD", "AFL-2.1", "AFL-3.0", "AGPL-1.0", "AGPL-3.0", "AGPL-3.0-only", "AGPL-3.0-or-later",
+    "Apache-1.1", "Apache-2.0", "Artistic-1.0", "Artistic-2.0", "BlueOak-1.0.0", "BSD-1-Clause",
+    "BSD-2-Clause", "BSD-3-Clause", "BSD-3-Clause-Clear", "BSD-4-Clause", "BSL-1.0", "CC-BY-3.0",
+    "CC-BY-4.0", "CC-BY-SA-3.0", "CC-BY-SA-4.0", "CC0-1.0", "CDDL-1.0", "CDDL-1.1", "EPL-1.0",
+    "EPL-2.0", "EUPL-1.1", "EUPL-1.2", "GPL-2.0", "GPL-2.0-only", "GPL-2.0-or-later", "GPL-3.0",
+    "GPL-3.0-only", "GPL-3.0-or-later", "ISC", "JSON", "LGPL-2.0", "LGPL-2.1", "LGPL-2.1-only",
+    "LGPL-2.1-or-later", "LGPL-3.0", "LGPL-3.0-only", "LGPL-3.0-or-later", "MIT", "MIT-0",
+    "MPL-1.1", "MPL-2.0", "MS-PL", "OFL-1.1", "PostgreSQL", "Python-2.0", "Ruby", "Unicode-3.0",
+    "Unicode-DFS-2016", "Unlicense", "UPL-1.0", "W3C", "WTFPL", "X11", "Zlib", "ZPL-2.1",
+];
+
+/// Returns a single SPDX expression from the `license` and legacy `licenses` fields. Either can
+/// be a string, a `{ "type": ... }` object or an array of those, and anything else is ignored.
+pub fn get_license_expression(license: Option<&Value>, licenses: Option<&Value>) -> Option<String> {
+    let mut names = license.map(get_license_names).unwrap_or_default();
+    if names.is_empty() {
+        names = licenses.map(get_license_names).unwrap_or_default();
+    }
+    match names.len() {
+        0 => None,
+        1 => Some(names[0].to_string()),
+        _ => Some(format!("({})", names.join(" OR "))),
+    }
+}
+
+fn get_license_names(value: &Value) -> Vec<&str> {
+    let name = match value {
+        Value::String(name) => name.as_str(),
+        Value::Object(object) => object.get("type").and_then(Value::as_str).unwrap_or(""),
+        Value::Array(entries) => return entries.iter().flat_map(get_license_names).collect(),
+        _ => "",
+    };
+    Some(name.trim()).filter(|name| !name.is_empty()).into_iter().collect()
+}
+
+/// A parsed SPDX license expression.
+#[derive(Debug, PartialEq)]
+pub enum LicenseExpression {
+    License(String),
+    And(Box<LicenseExpression>, Box<LicenseExpression>),
+    Or(Box<LicenseExpression>, Box<LicenseExpression>),
+}
+
+impl LicenseExpression {
+    /// Returns the LicenseExpression type, or None if the expression cannot be parsed.
+    pub fn parse(expression: &str) -> Option<Self> {
+        let spaced = expression.replace('(', " ( ").replace(')', " ) ");
+        let tokens: Vec<&str> = spaced.split_whitespace().collect();
+        let mut position = 0;
+        let parsed = parse_or(&tokens, &mut position)?;
+        match position == tokens.len() {
+            true => Some(parsed),
+            false => None,
+        }
+    }
+
+    /// Returns true if the licenses can be satisfied using only licenses the check accepts.
+    pub fn satisfies(&self, accepts: &dyn Fn(&str) -> bool) -> bool {
+        match self {
+            LicenseExpression::License(id) => accepts(id),
+            LicenseExpression::And(left, right) => left.satisfies(accepts) && right.satisfies(accepts),
+            LicenseExpression::Or(left, right) => left.satisfies(accepts) || right.satisfies(accepts),
+        }
+    }
+
+    /// Returns true if every license in the expression is a known SPDX identifier.
+    pub fn is_known(&self) -> bool {
+        self.satisfies(&|id| {
+            id.starts_with("LicenseRef-")
+                || KNOWN_LICENSES
+                    .iter()
+                    .any(|known| known.eq_ignore_ascii_case(id.trim_end_matches('+')))
+        })
+    }
+}
+
+fn parse_or(tokens: &[&str], position: &mut usize) -> Option<LicenseExpression> {
+    let mut left = parse_and(tokens, position)?;
+    while tokens.get(*position).is_some_and(|token| token.eq_ignore_ascii_case("OR")) {
+        *position += 1;
+        let right = parse_and(tokens, position)?;
+        left = LicenseExpression::Or(Box::new(left), Box::new(right));
+    }
+    Some(left)
+}
+
+fn parse_and(tokens: &[&str], position: &mut usize) -> Option<LicenseExpression> {
+    let mut left = parse_license(tokens, position)?;
+    while tokens.get(*position).is_some_and(|token| token.eq_ignore_ascii_case("AND")) {
+        *position += 1;
+        let right = parse_license(tokens, position)?;
+        left = LicenseExpression::And(Box::new(left), Box::new(right));
+    }
+    Some(left)
+}
+
+fn parse_license(tokens: &[&str], position: &mut usize) -> Option<LicenseExpression> {
+    let token = *tokens.get(*position)?;
+    *position += 1;
+
+    if token == "(" {
+        let inner = parse_or(tokens, position)?;
+        if tokens.get(*position) != Some(&")") {
+            return None;
+        }
+        *position += 1;
+        return Some(inner);
+    }
+
+    let is_operator = ["AND", "OR", "WITH", ")"]
+        .iter()
+        .any(|operator| operator.eq_ignore_ascii_case(token));
+    if is_operator {
+        return None;
+    }
+
+    // Exceptions only widen what a license permits, so the policy is applied to the license.
+    if tokens.get(*position).is_some_and(|token| token.eq_ignore_ascii_case("WITH")) {
+        tokens.get(*position + 1)?;
+        *position += 2;
+    }
+    Some(LicenseExpression::License(token.to_string()))
+}
+
+/// The licenses that may or may not be shipped, read from a JSON file such as
+/// `{ "allow": ["MIT", "ISC"], "deny": ["GPL-3.0-only"] }`.
+#[derive(Debug, Default, Deserialize)]
+pub struct LicensePolicy {
+    #[serde(default)]
+    pub allow: Vec<String>,
+
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+impl LicensePolicy {
+    /// Returns the LicensePolicy type from a JSON file.
+    pub fn from(path: &Path) -> Result<Self, Error> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Returns true if the license expression can be complied with under the policy.
+    pub fn permits(&self, license: Option<&str>) -> bool {
+        let contains = |list: &Vec<String>, id: &str| {
+            let id = id.trim_end_matches('+');
+            list.iter().any(|listed| listed.eq_ignore_ascii_case(id))
+        };
+        let accepts = |id: &str| {
+            (self.allow.is_empty() || contains(&self.allow, id)) && !contains(&self.deny, id)
+        };
+
+        match license.and_then(LicenseExpression::parse) {
+            Some(expression) => expression.satisfies(&accepts),
+            None => self.allow.is_empty(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum LicenseIssue {
+    Missing,
+    Unknown,
+    Violation,
+}
+
+/// The packages that share a license expression.
+pub struct LicenseGroup<'a> {
+    pub license: String,
+    pub modules: Vec<&'a StandardModule>,
+}
+
+impl<'a> LicenseGroup<'a> {
+    /// Returns the LicenseGroup type for each license, sorted by the number of packages.
+    pub fn get_groups(dependencies: &'a [StandardModule]) -> Vec<Self> {
+        let mut groups: Vec<LicenseGroup> = Vec::new();
+        for dependency in dependencies {
+            let license = dependency.license.clone().unwrap_or_default();
+            match groups.iter_mut().find(|group| group.license == license) {
+                Some(group) => group.modules.push(dependency),
+                None => groups.push(LicenseGroup {
+                    license,
+                    modules: vec![dependency],
+                }),
+            }
+        }
+        groups.sort_by(|a, b| b.modules.len().cmp(&a.modules.len()).then(a.license.cmp(&b.license)));
+        groups
+    }
+}
+
+impl<'a> PrintTable for LicenseGroup<'a> {
+    fn table_row(&self) -> Row {
+        let names: Vec<&str> = self.modules.iter().map(|module| module.name.as_str()).collect();
+        let license = match self.license.is_empty() {
+            true => "???",
+            false => &self.license,
+        };
+        Row::new(vec![
+            new_cell(license),
+            new_cell(&self.modules.len().to_string()),
+            new_cell(&names.join(", ")),
+        ])
+    }
+}
+
+/// A package whose license is missing, unknown or not permitted by the policy.
+pub struct LicenseFinding<'a> {
+    pub module: &'a StandardModule,
+    pub issue: LicenseIssue,
+    pub production: bool,
+}
+
+impl<'a> LicenseFinding<'a> {
+    pub fn from(module: &'a StandardModule, policy: Option<&LicensePolicy>, production: bool) -> Option<Self> {
+        let license = module.license.as_deref();
+        let issue = match policy {
+            Some(policy) if !policy.permits(license) => LicenseIssue::Violation,
+            _ => match license {
+                None => LicenseIssue::Missing,
+                Some(license) => match LicenseExpression::parse(license) {
+                    Some(expression) if expression.is_known() => return None,
+                    _ => LicenseIssue::Unknown,
+                },
+            },
+        };
+        Some(LicenseFinding {
+            module,
+            issue,
+            production,
+        })
+    }
+}
+
+impl<'a> PrintTable for LicenseFinding<'a> {
+    fn table_row(&self) -> Row {
+        let (issue, colour) = match self.issue {
+            LicenseIssue::Missing => ("missing", color::YELLOW),
+            LicenseIssue::Unknown => ("unknown", color::YELLOW),
+            LicenseIssue::Violation => ("not permitted", color::RED),
+        };
+        let version = match &self.module.version {
+            Some(version) => version.to_string(),
+            None => String::new(),
+        };
+        let dep_type = match self.production {
+            true => "production",
+            false => "development",
+        };
+        Row::new(vec![
+            new_cell(&self.module.name),
+            new_cell(&version),
+            new_cell(self.module.license.as_deref().unwrap_or("")),
+            new_cell(issue).with_style(Attr::ForegroundColor(colour)),
+            new_cell(dep_type),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lockfile::Lockfile;
+    use crate::node_module::{get_nested_deps, is_production};
+    use crate::pjson_detail::PjsonDetail;
+    use crate::test_dir::{write, TestDir};
+
+    fn parse(expression: &str) -> LicenseExpression {
+        LicenseExpression::parse(expression).unwrap()
+    }
+
+    fn expression(pjson: &str) -> Option<String> {
+        let pjson = PjsonDetail::parse(pjson).unwrap();
+        get_license_expression(pjson.license.as_ref(), pjson.licenses.as_ref())
+    }
+
+    #[test]
+    fn reads_legacy_license_fields() {
+        let pjson = |fields: &str| format!(r#"{{ "name": "a", "version": "1.0.0", {} }}"#, fields);
+        assert_eq!(expression(&pjson(r#""license": "MIT""#)).as_deref(), Some("MIT"));
+        assert_eq!(expression(&pjson(r#""license": { "type": "MIT" }"#)).as_deref(), Some("MIT"));
+        assert_eq!(
+            expression(&pjson(r#""licenses": [{ "type": "MIT" }, "Apache-2.0"]"#)).as_deref(),
+            Some("(MIT OR Apache-2.0)")
+        );
+        assert_eq!(expression(&pjson(r#""licenses": { "type": "MIT" }"#)).as_deref(), Some("MIT"));
+        assert_eq!(expression(&pjson(r#""licenses": "MIT""#)).as_deref(), Some("MIT"));
+        assert_eq!(
+            expression(&pjson(r#""license": { "url": "http://example.com" }, "licenses": "ISC""#)).as_deref(),
+            Some("ISC")
+        );
+        assert_eq!(expression(&pjson(r#""license": { "url": "http://example.com" }"#)), None);
+        assert_eq!(expression(&pjson(r#""license": 3"#)), None);
+        assert_eq!(expression(&pjson(r#""private": true"#)), None);
+    }
+
+    #[test]
+    fn parses_expressions() {
+        assert_eq!(
+            parse("(MIT OR Apache-2.0) AND BSD-3-Clause"),
+            LicenseExpression::And(
+                Box::new(LicenseExpression::Or(
+                    Box::new(LicenseExpression::License(String::from("MIT"))),
+                    Box::new(LicenseExpression::License(String::from("Apache-2.0"))),
+                )),
+                Box::new(LicenseExpression::License(String::from("BSD-3-Clause"))),
+            )
+        );
+        assert_eq!(
+            parse("GPL-2.0-only WITH Classpath-exception-2.0"),
+            LicenseExpression::License(String::from("GPL-2.0-only"))
+        );
+        assert!(LicenseExpression::parse("MIT OR").is_none());
+        assert!(LicenseExpression::parse("(MIT").is_none());
+        assert!(LicenseExpression::parse("SEE LICENSE IN LICENSE.md").is_none());
+    }
+
+    #[test]
+    fn recognises_known_licenses() {
+        assert!(parse("MIT OR Apache-2.0").is_known());
+        assert!(parse("GPL-2.0+").is_known());
+        assert!(!parse("MIT AND Proprietary").is_known());
+    }
+
+    #[test]
+    fn applies_policies() {
+        let policy = LicensePolicy {
+            allow: vec![String::from("MIT"), String::from("Apache-2.0")],
+            deny: vec![],
+        };
+        assert!(policy.permits(Some("MIT")));
+        assert!(policy.permits(Some("GPL-3.0-only OR MIT")));
+        assert!(!policy.permits(Some("GPL-3.0-only AND MIT")));
+        assert!(!policy.permits(None));
+
+        let policy = LicensePolicy {
+            allow: vec![],
+            deny: vec![String::from("GPL-3.0-only")],
+        };
+        assert!(policy.permits(Some("ISC")));
+        assert!(!policy.permits(Some("GPL-3.0-only")));
+        assert!(policy.permits(None));
+    }
+
+    #[test]
+    fn checks_nested_packages() {
+        let root = TestDir::new("nested-licenses");
+        let pjson = |name: &str, license: &str| {
+            format!(r#"{{ "name": "{}", "version": "1.0.0", "license": "{}" }}"#, name, license)
+        };
+        write(root.join("node_modules/a/package.json"), &pjson("a", "MIT"));
+        write(root.join("node_modules/a/node_modules/b/package.json"), &pjson("b", "GPL-3.0-only"));
+        write(root.join("node_modules/b/package.json"), &pjson("b", "MIT"));
+        let lockfile = Lockfile::parse(
+            r#"{ "packages": {
+                "node_modules/a": { "version": "1.0.0" },
+                "node_modules/a/node_modules/b": { "version": "1.0.0" },
+                "node_modules/b": { "version": "1.0.0", "dev": true }
+            } }"#,
+        )
+        .unwrap();
+        let policy = LicensePolicy {
+            allow: vec![],
+            deny: vec![String::from("GPL-3.0-only")],
+        };
+
+        let nested = get_nested_deps(&root.join("node_modules")).unwrap();
+        assert_eq!(nested.len(), 1);
+        assert_eq!(nested[0].path, root.join("node_modules/a/node_modules/b"));
+        let production = is_production(&nested[0], Some(&lockfile));
+        assert!(production);
+        let finding = LicenseFinding::from(&nested[0], Some(&policy), production).unwrap();
+        assert_eq!(finding.issue, LicenseIssue::Violation);
+    }
+}
@@ -0,0 +1,136 @@
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Details of a package folder in node_modules that is a symlink rather than an installed copy.
+#[derive(Debug)]
+pub struct Link {
+    pub target: PathBuf,
+    pub in_repo: bool,
+    pub dangling: bool,
+}
+
+impl Link {
+    /// Returns the Link type if the folder at `path` is a symlink.
+    pub fn from(path: &Path) -> Option<Link> {
+        let metadata = fs::symlink_metadata(path).ok()?;
+        if !metadata.file_type().is_symlink() {
+            return None;
+        }
+
+        let raw_target = fs::read_link(path).ok()?;
+        let target = match raw_target.is_absolute() {
+            true => raw_target,
+            false => normalise(&path.parent()?.join(raw_target)),
+        };
+        let dangling = !target.exists();
+
+        let in_repo = match get_project_root(path) {
+            Some(root) => {
+                let root = root.canonicalize().unwrap_or(root);
+                let resolved = target.canonicalize().unwrap_or_else(|_| target.clone());
+                resolved.starts_with(&root)
+            }
+            None => false,
+        };
+
+        Some(Link {
+            target,
+            in_repo,
+            dangling,
+        })
+    }
+}
+
+impl fmt::Display for Link {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let location = match self.in_repo {
+            true => "repo",
+            false => "external",
+        };
+        match self.dangling {
+            true => write!(f, "dangling → {}", self.target.display()),
+            false => write!(f, "{} → {}", location, self.target.display()),
+        }
+    }
+}
+
+/// Returns the folder containing the node_modules folder that `path` was found in.
+fn get_project_root(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|ancestor| ancestor.file_name() == Some(OsStr::new("node_modules")))
+        .and_then(|node_modules| node_modules.parent())
+        .map(PathBuf::from)
+}
+
+/// Resolves `.` and `..` components without touching the filesystem, so dangling targets can
+/// still be reported as a clean path.
+fn normalise(path: &Path) -> PathBuf {
+    let mut normalised = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalised.pop() {
+                    normalised.push("..");
+                }
+            }
+            other => normalised.push(other.as_os_str()),
+        }
+    }
+    normalised
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_module::link_filter;
+
+    #[test]
+    fn normalises_paths() {
+        assert_eq!(normalise(Path::new("/app/node_modules/./../packages/a")), PathBuf::from("/app/packages/a"));
+        assert_eq!(normalise(Path::new("a/../../b")), PathBuf::from("../b"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reads_links() {
+        use crate::test_dir::TestDir;
+        use std::os::unix::fs::symlink;
+
+        let root = TestDir::new("link");
+        let project = root.join("app");
+        let node_modules = project.join("node_modules");
+        for folder in ["app/packages/a", "external/b", "app/node_modules/@s", "app/node_modules/e"].iter() {
+            fs::create_dir_all(root.join(folder)).unwrap();
+        }
+
+        symlink(project.join("packages/a"), node_modules.join("a")).unwrap();
+        symlink(root.join("external/b"), node_modules.join("b")).unwrap();
+        symlink(root.join("gone"), node_modules.join("c")).unwrap();
+        symlink("../../packages/a", node_modules.join("@s/d")).unwrap();
+
+        let in_repo = Link::from(&node_modules.join("a")).unwrap();
+        assert!(in_repo.in_repo && !in_repo.dangling);
+        assert_eq!(in_repo.to_string(), format!("repo → {}", project.join("packages/a").display()));
+
+        let external = Link::from(&node_modules.join("b")).unwrap();
+        assert!(!external.in_repo && !external.dangling);
+
+        let dangling = Link::from(&node_modules.join("c")).unwrap();
+        assert!(dangling.dangling);
+        assert_eq!(dangling.to_string(), format!("dangling → {}", root.join("gone").display()));
+
+        let relative = Link::from(&node_modules.join("@s/d")).unwrap();
+        assert_eq!(relative.target, project.join("packages/a"));
+        assert!(relative.in_repo);
+
+        let installed = Link::from(&node_modules.join("e"));
+        assert!(installed.is_none());
+
+        assert!(link_filter(&Some(dangling), true));
+        assert!(!link_filter(&installed, true));
+        assert!(link_filter(&installed, false));
+    }
+}
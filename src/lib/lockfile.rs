@@ -0,0 +1,310 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+use serde::Deserialize;
+
+/// The lockfiles npm reads, in the order it prefers them.
+pub const LOCKFILES: [&str; 2] = ["npm-shrinkwrap.json", "package-lock.json"];
+
+/// The packages recorded in a package-lock.json or npm-shrinkwrap.json, keyed by their install
+/// path relative to the project, e.g. `node_modules/a/node_modules/b`.
+#[derive(Debug, Default)]
+pub struct Lockfile {
+    pub packages: HashMap<String, LockedPackage>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct LockedPackage {
+    pub version: Option<String>,
+    pub resolved: Option<String>,
+    pub integrity: Option<String>,
+
+    #[serde(default)]
+    pub dev: bool,
+
+    /// The ranges of the packages this one requires, from `dependencies` in lockfile version 2
+    /// and later or `requires` in version 1.
+    #[serde(default, alias = "requires")]
+    pub dependencies: HashMap<String, String>,
+
+    #[serde(default, rename = "optionalDependencies")]
+    pub optional_dependencies: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct RawLockfile {
+    packages: Option<HashMap<String, LockedPackage>>,
+    dependencies: Option<HashMap<String, RawV1Dependency>>,
+}
+
+/// Lockfile version 1 nests dependencies instead of listing them by path.
+#[derive(Deserialize)]
+struct RawV1Dependency {
+    #[serde(flatten)]
+    package: LockedPackage,
+    dependencies: Option<HashMap<String, RawV1Dependency>>,
+}
+
+impl Lockfile {
+    /// Returns the Lockfile for the project at `base_path`, if it has one.
+    pub fn from(base_path: &Path) -> Result<Option<Lockfile>, Error> {
+        for file_name in LOCKFILES.iter() {
+            let path = base_path.join(file_name);
+            if path.is_file() {
+                let contents = fs::read_to_string(path)?;
+                return Ok(Some(Self::parse(&contents)?));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Parses the contents of a lockfile of any lockfileVersion.
+    pub fn parse(contents: &str) -> Result<Lockfile, Error> {
+        let raw: RawLockfile = serde_json::from_str(contents)?;
+
+        if let Some(packages) = raw.packages {
+            let packages = packages
+                .into_iter()
+                .filter(|(path, _)| !path.is_empty())
+                .collect();
+            return Ok(Lockfile { packages });
+        }
+
+        let dependencies = raw.dependencies.ok_or_else(|| {
+            Error::new(ErrorKind::InvalidData, "not a lockfile, as it has no packages or dependencies")
+        })?;
+        let mut packages = HashMap::new();
+        flatten_v1("", dependencies, &mut packages);
+        Ok(Lockfile { packages })
+    }
+
+    /// Returns the entry for a package installed at the top level of node_modules.
+    pub fn get(&self, name: &str) -> Option<&LockedPackage> {
+        self.packages.get(&format!("node_modules/{}", name))
+    }
+
+    /// Returns the entry for the package installed in a folder, such as
+    /// `/src/app/node_modules/a/node_modules/b`, matching the longest install path it ends with.
+    pub fn get_installed(&self, folder: &Path) -> Option<&LockedPackage> {
+        let components: Vec<&str> = folder.iter().map(|component| component.to_str()).collect::<Option<_>>()?;
+        let mut install_paths = Vec::new();
+        let mut start = components.len();
+        while start >= 2 {
+            let mut name = start - 1;
+            if name >= 2 && components[name - 1].starts_with('@') {
+                name -= 1;
+            }
+            if components[name - 1] != "node_modules" {
+                break;
+            }
+            start = name - 1;
+            install_paths.push(components[start..].join("/"));
+        }
+        install_paths.iter().rev().find_map(|install_path| self.packages.get(install_path))
+    }
+
+    /// Returns the packages installed at the top level of node_modules and their names, sorted
+    /// by name.
+    pub fn top_level(&self) -> Vec<(&str, &LockedPackage)> {
+        let mut packages: Vec<(&str, &LockedPackage)> = self
+            .packages
+            .iter()
+            .filter_map(|(path, package)| {
+                let name = path.strip_prefix("node_modules/")?;
+                match name.contains("/node_modules/") {
+                    true => None,
+                    false => Some((name, package)),
+                }
+            })
+            .collect();
+        packages.sort_by(|a, b| a.0.cmp(b.0));
+        packages
+    }
+
+    /// Returns the install path that `name` resolves to when required by the package installed
+    /// at `from`. `from` is empty for the project itself.
+    pub fn resolve(&self, from: &str, name: &str) -> Option<String> {
+        resolve_install_path(from, name, |path| self.packages.contains_key(path))
+    }
+
+    /// Returns the shortest chain of install paths from one of the project's own dependencies to
+    /// each package reachable from them, keyed by install path.
+    pub fn dependency_paths(&self, roots: &[String]) -> HashMap<String, Vec<String>> {
+        let mut previous: HashMap<String, Option<String>> = HashMap::new();
+        let mut queue = VecDeque::new();
+
+        let mut roots: Vec<&String> = roots.iter().collect();
+        roots.sort();
+        for root in roots {
+            if let Some(path) = self.resolve("", root) {
+                if !previous.contains_key(&path) {
+                    previous.insert(path.clone(), None);
+                    queue.push_back(path);
+                }
+            }
+        }
+
+        while let Some(path) = queue.pop_front() {
+            let package = &self.packages[&path];
+            let mut names: Vec<&String> = package
+                .dependencies
+                .keys()
+                .chain(package.optional_dependencies.keys())
+                .collect();
+            names.sort();
+            for name in names {
+                if let Some(child) = self.resolve(&path, name) {
+                    if !previous.contains_key(&child) {
+                        previous.insert(child.clone(), Some(path.clone()));
+                        queue.push_back(child);
+                    }
+                }
+            }
+        }
+
+        previous
+            .keys()
+            .map(|path| {
+                let mut chain = vec![path.clone()];
+                while let Some(Some(parent)) = previous.get(chain.last().unwrap()) {
+                    chain.push(parent.clone());
+                }
+                chain.reverse();
+                (path.clone(), chain)
+            })
+            .collect()
+    }
+}
+
+/// Returns the install path that `name` resolves to from the package installed at `from`, such as
+/// `node_modules/a`, looking in each parent node_modules folder in turn as Node does.
+pub fn resolve_install_path<F>(from: &str, name: &str, exists: F) -> Option<String>
+where
+    F: Fn(&str) -> bool,
+{
+    let mut base = from;
+    loop {
+        let path = match base.is_empty() {
+            true => format!("node_modules/{}", name),
+            false => format!("{}/node_modules/{}", base, name),
+        };
+        if exists(&path) {
+            return Some(path);
+        }
+        if base.is_empty() {
+            return None;
+        }
+        base = match base.rfind("/node_modules/") {
+            Some(i) => &base[..i],
+            None => "",
+        };
+    }
+}
+
+fn flatten_v1(
+    prefix: &str,
+    dependencies: HashMap<String, RawV1Dependency>,
+    packages: &mut HashMap<String, LockedPackage>,
+) {
+    for (name, dependency) in dependencies {
+        let path = format!("{}node_modules/{}", prefix, name);
+        if let Some(children) = dependency.dependencies {
+            flatten_v1(&format!("{}/", path), children, packages);
+        }
+        packages.insert(path, dependency.package);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_v1_lockfile() {
+        let lockfile = Lockfile::parse(
+            r#"{
+                "lockfileVersion": 1,
+                "dependencies": {
+                    "a": {
+                        "version": "1.0.0",
+                        "resolved": "https://registry.npmjs.org/a/-/a-1.0.0.tgz",
+                        "dependencies": {
+                            "b": { "version": "2.0.0", "dev": true }
+                        }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let a = lockfile.get("a").unwrap();
+        assert_eq!(a.version.as_deref(), Some("1.0.0"));
+        assert_eq!(a.resolved.as_deref(), Some("https://registry.npmjs.org/a/-/a-1.0.0.tgz"));
+
+        let b = &lockfile.packages["node_modules/a/node_modules/b"];
+        assert_eq!(b.version.as_deref(), Some("2.0.0"));
+        assert!(b.dev);
+    }
+
+    #[test]
+    fn parses_v2_lockfile() {
+        let lockfile = Lockfile::parse(
+            r#"{
+                "lockfileVersion": 2,
+                "packages": {
+                    "": { "name": "app", "version": "1.0.0" },
+                    "node_modules/@scope/a": { "version": "1.0.0", "integrity": "sha512-x" }
+                },
+                "dependencies": {}
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(lockfile.packages.len(), 1);
+        assert_eq!(lockfile.get("@scope/a").unwrap().integrity.as_deref(), Some("sha512-x"));
+    }
+
+    #[test]
+    fn rejects_other_json() {
+        assert!(Lockfile::parse(r#"{ "name": "app", "version": "1.0.0" }"#).is_err());
+        assert!(Lockfile::parse(r#"{ "lockfileVersion": 1, "dependencies": {} }"#).is_ok());
+    }
+
+    #[test]
+    fn finds_dependency_paths() {
+        let lockfile = Lockfile::parse(
+            r#"{
+                "lockfileVersion": 3,
+                "packages": {
+                    "": { "dependencies": { "a": "^1.0.0", "c": "^1.0.0" } },
+                    "node_modules/a": { "version": "1.0.0", "dependencies": { "b": "^1.0.0" } },
+                    "node_modules/a/node_modules/b": { "version": "1.0.0", "dependencies": { "c": "^1.0.0" } },
+                    "node_modules/b": { "version": "2.0.0" },
+                    "node_modules/c": { "version": "1.0.0" }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            lockfile.resolve("node_modules/a/node_modules/b", "c").as_deref(),
+            Some("node_modules/c")
+        );
+        let paths = lockfile.dependency_paths(&[String::from("a")]);
+        assert_eq!(
+            paths["node_modules/a/node_modules/b"],
+            vec![String::from("node_modules/a"), String::from("node_modules/a/node_modules/b")]
+        );
+        assert_eq!(
+            paths["node_modules/c"],
+            vec![
+                String::from("node_modules/a"),
+                String::from("node_modules/a/node_modules/b"),
+                String::from("node_modules/c")
+            ]
+        );
+        assert!(!paths.contains_key("node_modules/b"));
+    }
+}
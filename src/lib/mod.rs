@@ -1,35 +1,257 @@
-#[macro_use]
-extern crate prettytable;
+mod advisories;
 mod cli;
+mod dependency_spec;
+mod disk_usage;
+mod engines;
+mod fleet;
+mod freshness;
+mod git;
+mod global_manifest;
+mod global_prefix;
+mod history;
+mod installations;
+mod licenses;
+mod link;
+mod lockfile;
 mod node_module;
+mod notices;
+mod npm_cache;
+mod npmrc;
 mod pjson_detail;
+mod range;
+mod registry;
+mod registry_audit;
+mod release_notes;
 mod semver;
+mod snapshot;
+mod source;
+#[cfg(test)]
+mod test_dir;
+mod toolchain;
 
+use std::collections::HashMap;
 use std::default::Default;
-use std::io::{self, Error, Write};
-use std::path::PathBuf;
+use std::fs;
+use std::io::{self, Error, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use prettytable::Table;
+use prettytable::{Cell, Row, Table};
 use regex::Regex;
 use which::which;
 
-pub use cli::Cli;
+use node_module::NodeModule;
+
+pub use advisories::{AdvisoryDatabase, InstalledPackage};
+pub use cli::{Cli, Command, SnapshotCommand};
+pub use dependency_spec::DependencySpec;
+pub use disk_usage::{DiskUsage, PackageSize, SubtreeSize};
+pub use engines::EngineFinding;
+pub use fleet::{FleetMatch, Query};
+pub use freshness::Freshness;
+pub use global_manifest::GlobalManifest;
+pub use global_prefix::GlobalManager;
+pub use installations::Installation;
+pub use licenses::{LicenseExpression, LicensePolicy};
+pub use link::Link;
+pub use lockfile::Lockfile;
+pub use node_module::diffed_matrix::DiffedRow;
 pub use node_module::diffed_pair::DiffedPair;
+pub use node_module::freshness_module::FreshnessModule;
+pub use node_module::global_bin::{BinLink, BinPath, GlobalBin};
+pub use node_module::global_diffed_pair::GlobalDiffedPair;
 pub use node_module::global_module::GlobalModule;
+pub use node_module::global_pair::GlobalPair;
+pub use node_module::outdated_module::OutdatedModule;
 pub use node_module::standard_module::StandardModule;
+pub use notices::Notices;
+pub use npm_cache::NpmCache;
+pub use npmrc::Npmrc;
 pub use pjson_detail::PjsonDetail;
+pub use range::Range;
+pub use semver::Semver;
+pub use registry::{Packument, RegistryClient};
+pub use registry_audit::{RegistryFinding, ResolvedPackage};
+pub use snapshot::Snapshot;
+pub use source::Source;
+pub use toolchain::{Requirement, ToolchainCheck};
 
 pub fn run_global() -> Result<(), Error> {
-    let base_path = get_node_modules_path(&get_global_path());
+    let cli = Cli::get();
+    let manifest = match &cli.import {
+        Some(path) => Some(GlobalManifest::read(path)?),
+        None => None,
+    };
+    // An imported manifest is compared with the global packages of the manager it came from.
+    let manager_name = match (&manifest, &cli.global_manager) {
+        (Some(manifest), Some(name)) if *name != manifest.manager => {
+            return Err(Error::other(format!(
+                "the manifest lists {} global packages, not {}",
+                manifest.manager, name
+            )));
+        }
+        (Some(manifest), _) => manifest.manager.as_str(),
+        (None, _) => cli.global_manager(),
+    };
+    let manager = GlobalManager::from(manager_name)
+        .ok_or_else(|| Error::other(format!("{} is not a supported package manager", manager_name)))?;
+    let root = global_prefix::get_global_root(&manager)?;
     let mut dependencies = Vec::<GlobalModule>::new();
-    collect_dependencies(&base_path, &mut dependencies, None)?;
-    print_table(&dependencies);
+    collect_dependencies(&root.node_modules, &mut dependencies, None)?;
+
+    if let Some(path) = &cli.export {
+        GlobalManifest::from(manager_name, &dependencies).write(path)?;
+        print_completion_message(format!(
+            "\nWrote {} global packages to {}.\n",
+            dependencies.len(),
+            path.display()
+        ))?;
+        return Ok(());
+    }
+    if let Some(manifest) = &manifest {
+        return run_global_plan(manifest, &dependencies, &manager);
+    }
+
+    if !cli.bins {
+        print_table(&dependencies);
+        return Ok(());
+    }
+
+    let bins: Vec<GlobalBin> = dependencies
+        .iter()
+        .flat_map(|dependency| GlobalBin::from(dependency, &root.bin))
+        .collect();
+    print_table(&bins);
+
+    let broken = bins
+        .iter()
+        .filter(|bin| !matches!(bin.link, BinLink::Linked | BinLink::Shim))
+        .count();
+    let shadowed = bins
+        .iter()
+        .filter(|bin| matches!(bin.path, BinPath::Shadowed(_)))
+        .count();
+    print_completion_message(format!(
+        "\n{} executables in {}: {} not linked to their package and {} shadowed on PATH.\n",
+        bins.len(),
+        root.bin.display(),
+        broken,
+        shadowed,
+    ))?;
+    Ok(())
+}
+
+fn run_global_plan(
+    manifest: &GlobalManifest,
+    installed: &[GlobalModule],
+    manager: &GlobalManager,
+) -> Result<(), Error> {
+    let cli = Cli::get();
+    let plan = manifest.plan(installed);
+    print_table(&plan);
+
+    let unlisted = manifest.unlisted(installed);
+    if !unlisted.is_empty() {
+        let names: Vec<&str> = unlisted.iter().map(|module| module.name.as_str()).collect();
+        print_completion_message(format!("\nInstalled but not in the manifest: {}\n", names.join(", ")))?;
+    }
+
+    let commands: Vec<String> = plan.iter().filter_map(|step| step.command(manager)).collect();
+    if commands.is_empty() {
+        print_completion_message(String::from("\nEvery package in the manifest is installed.\n"))?;
+        return Ok(());
+    }
+    match &cli.script {
+        Some(path) => {
+            fs::write(path, global_manifest::to_script(&commands))?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(path, fs::Permissions::from_mode(0o755))?;
+            }
+            print_completion_message(format!(
+                "\nWrote {} install commands to {}.\n",
+                commands.len(),
+                path.display()
+            ))?;
+        }
+        None => print_completion_message(format!("\n{}\n", commands.join("\n")))?,
+    }
+    Ok(())
+}
+
+pub fn run_compare_global() -> Result<(), Error> {
+    let cli = Cli::get();
+    let app_pjson = PjsonDetail::from(&cli.path)?;
+    let locals = get_standard_deps(&app_pjson)?;
+
+    let manager = GlobalManager::from(cli.global_manager()).ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("{} is not a supported package manager", cli.global_manager()),
+        )
+    })?;
+    let root = global_prefix::get_global_root(&manager)?;
+    let mut globals = Vec::<GlobalModule>::new();
+    collect_dependencies(&root.node_modules, &mut globals, None)?;
+
+    let pairs = GlobalPair::get_pairs(&locals, &globals);
+    print_table(&pairs);
+    print_completion_message(format!(
+        "\n{} global packages are also installed in {}, {} at a different version.\n",
+        pairs.len(),
+        app_pjson.name,
+        pairs.iter().filter(|pair| pair.is_different()).count(),
+    ))?;
+    Ok(())
+}
+
+pub fn run_installations() -> Result<(), Error> {
+    let installations = Installation::find_all();
+    if installations.is_empty() {
+        print_completion_message(String::from(
+            "\nNo Node installations were found in the nvm, fnm, Volta or asdf folders.\n",
+        ))?;
+        return Ok(());
+    }
+
+    let active = which("node").ok();
+    for installation in installations.iter() {
+        let version = match &installation.version {
+            Some(version) => version.to_string(),
+            None => String::from("unknown version"),
+        };
+        let is_active = active.as_ref().is_some_and(|node| installation.provides(node));
+        print_completion_message(format!(
+            "\n{} node {} in {}{}\n",
+            installation.manager,
+            version,
+            installation.prefix.display(),
+            if is_active { " (active)" } else { "" },
+        ))?;
+
+        let mut dependencies = Vec::<GlobalModule>::new();
+        match collect_dependencies(&installation.node_modules(), &mut dependencies, None) {
+            Ok(()) if dependencies.is_empty() => print_completion_message(String::from("No global packages.\n"))?,
+            Ok(()) => print_table(&dependencies),
+            Err(err) => eprintln!("Failed to read {}: {}", installation.node_modules().display(), err),
+        }
+    }
     Ok(())
 }
 
 pub fn run_standard() -> Result<(), Error> {
     let app_pjson = PjsonDetail::from(&Cli::get().path)?;
-    let dependencies = get_standard_deps(&app_pjson)?;
+    let mut dependencies = get_standard_deps(&app_pjson)?;
+    filter_by_source(&mut dependencies);
+    if Cli::get().cached {
+        fill_cached_versions(&mut dependencies)?;
+    }
+    // The link column is only worth its width when links are asked for or there are some.
+    let show_link = Cli::get().linked || dependencies.iter().any(|dependency| dependency.link.is_some());
+    for dependency in dependencies.iter_mut() {
+        dependency.show_link = show_link;
+    }
     print_table(&dependencies);
     print_completion_message(format!(
         "\n{} matches found in version {} of {}.\n",
@@ -40,41 +262,790 @@ pub fn run_standard() -> Result<(), Error> {
     Ok(())
 }
 
-pub fn run_diff(path: &PathBuf) -> Result<(), Error> {
-    let app_pjson = PjsonDetail::from(&Cli::get().path)?;
-    let dependencies = get_standard_deps(&app_pjson)?;
-    let diff_path = get_node_modules_path(&path);
-    let diff_pjson = PjsonDetail::from(&path)?;
-    let mut diff_dependencies = Vec::<StandardModule>::new();
-    collect_dependencies(&diff_path, &mut diff_dependencies, Some(&diff_pjson))?;
+pub fn run_diff(paths: &[PathBuf]) -> Result<(), Error> {
+    let (path, diff_path) = get_diff_paths(paths)?;
+    let mut dependencies = get_diff_deps(path)?;
+    let mut diff_dependencies = get_diff_deps(diff_path)?;
+    filter_by_source(&mut dependencies);
+    filter_by_source(&mut diff_dependencies);
+    let diffed_pairs = DiffedPair::get_pairs(&dependencies, &diff_dependencies);
+    print_table(&diffed_pairs);
+    Ok(())
+}
+
+pub fn run_diff_rev(revisions: &str) -> Result<(), Error> {
+    let cli = Cli::get();
+    let (from, to) = git::parse_revisions(revisions)?;
+    let (dependencies, _) = get_revision_deps(&cli.path, Some(from))?;
+    let (diff_dependencies, _) = get_revision_deps(&cli.path, to)?;
     let diffed_pairs = DiffedPair::get_pairs(&dependencies, &diff_dependencies);
     print_table(&diffed_pairs);
     Ok(())
 }
 
+pub fn run_release_notes(
+    from: &str,
+    to: Option<&str>,
+    production: bool,
+    markdown: bool,
+    output: Option<&Path>,
+) -> Result<(), Error> {
+    let old = get_release_side(Some(from), production)?;
+    let new = get_release_side(to, production)?;
+
+    let changes = release_notes::get_changes(&old, &new);
+    let document = match markdown {
+        true => release_notes::to_markdown(&changes),
+        false => release_notes::to_text(&changes),
+    };
+
+    match output {
+        Some(path) => {
+            fs::write(path, document)?;
+            print_completion_message(format!(
+                "\nWrote {} dependency changes to {}.\n",
+                changes.len(),
+                path.display()
+            ))?;
+        }
+        None => print_completion_message(document)?,
+    }
+    Ok(())
+}
+
+/// Returns the dependencies for one side of the release notes, which is a snapshot, a lockfile, a
+/// project folder or a git revision of the project, or the project's lockfile when not given.
+fn get_release_side(side: Option<&str>, production: bool) -> Result<Vec<StandardModule>, Error> {
+    let cli = Cli::get();
+    let path = match side {
+        Some(side) => Path::new(side),
+        None => &cli.path,
+    };
+    let filter = |dependencies: Vec<StandardModule>, lockfile: &Lockfile| -> Vec<StandardModule> {
+        dependencies
+            .into_iter()
+            .filter(|dependency| !production || node_module::is_production(dependency, Some(lockfile)))
+            .collect()
+    };
+
+    if path.is_dir() {
+        let (dependencies, lockfile) = get_revision_deps(path, None)?;
+        return Ok(filter(dependencies, &lockfile));
+    }
+    if path.is_file() {
+        let contents = fs::read_to_string(path)?;
+        if let Ok(snapshot) = Snapshot::parse(&contents) {
+            let project = path.parent().unwrap_or(Path::new("."));
+            return match production {
+                true => snapshot.production_modules(project).ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidInput,
+                        format!(
+                            "{} does not record which packages are dev dependencies, so it can't be used with --production",
+                            path.display()
+                        ),
+                    )
+                }),
+                false => Ok(snapshot.modules(project)),
+            };
+        }
+        let lockfile = Lockfile::parse(&contents)?;
+        let app_pjson = path.parent().and_then(|folder| PjsonDetail::from(folder).ok());
+        let dependencies = node_module::get_locked_deps(app_pjson.as_ref(), &lockfile);
+        return Ok(filter(dependencies, &lockfile));
+    }
+
+    let (dependencies, lockfile) = get_revision_deps(&cli.path, side)?;
+    Ok(filter(dependencies, &lockfile))
+}
+
+/// Returns the dependencies locked at a git revision of the project, or in its working tree when
+/// there is no revision, with the lockfile they were read from. They are filtered by name and
+/// dependency type like installed packages.
+fn get_revision_deps(project: &Path, revision: Option<&str>) -> Result<(Vec<StandardModule>, Lockfile), Error> {
+    let (app_pjson, lockfile) = match revision {
+        Some(revision) => git::read_revision(project, revision)?,
+        None => {
+            let lockfile = Lockfile::from(project)?
+                .ok_or_else(|| Error::other(format!("there is no lockfile in {}", project.display())))?;
+            (PjsonDetail::from(project)?, lockfile)
+        }
+    };
+    let filter_re = Regex::new(&Cli::get().filter).unwrap();
+    let dependencies = node_module::get_locked_deps(Some(&app_pjson), &lockfile)
+        .into_iter()
+        .filter(|dependency| {
+            filter_re.is_match(&dependency.name) && node_module::standard_filter(&dependency.dep_type)
+        })
+        .collect();
+    Ok((dependencies, lockfile))
+}
+
+pub fn run_history(package: &str) -> Result<(), Error> {
+    let cli = Cli::get();
+    let mut file_names = vec!["package.json"];
+    file_names.extend(lockfile::LOCKFILES.iter());
+    let commits = git::log(&cli.path, &file_names)?;
+
+    let mut states: Vec<(git::Commit, history::PackageState)> = Vec::new();
+    for commit in commits {
+        let app_pjson = match git::show_file(&cli.path, &commit.hash, "package.json")? {
+            Some(contents) => PjsonDetail::parse(&contents)
+                .map(Some)
+                .map_err(|err| eprintln!("Failed to read package.json at {}: {}", commit.hash, err)),
+            None => Ok(None),
+        };
+        let lockfile = git::read_lockfile(&cli.path, &commit.hash)
+            .map_err(|err| eprintln!("Failed to read the lockfile at {}: {}", commit.hash, err));
+        let mut state = history::PackageState::from(
+            package,
+            app_pjson.as_ref().ok().and_then(Option::as_ref),
+            lockfile.as_ref().ok().and_then(Option::as_ref),
+        );
+
+        // A file that can't be read keeps its state from the commit before, rather than looking
+        // like the package was removed.
+        if let Some((_, previous)) = states.last() {
+            if app_pjson.is_err() {
+                state.declared = previous.declared.clone();
+            }
+            if lockfile.is_err() {
+                state.version = previous.version.clone();
+            }
+        }
+        states.push((commit, state));
+    }
+
+    let history = history::get_history(&states);
+    print_table(&history);
+    print_completion_message(format!(
+        "\n{} of {} commits to package.json and the lockfile changed {}.\n",
+        history.len(),
+        states.len(),
+        package,
+    ))?;
+    Ok(())
+}
+
+pub fn run_diff_global(paths: &[PathBuf]) -> Result<(), Error> {
+    let cli = Cli::get();
+    let installations = Installation::find_all();
+    let roots = match paths {
+        [path, diff_path] => (
+            global_prefix::get_diff_root(path, &installations)?,
+            global_prefix::get_diff_root(diff_path, &installations)?,
+        ),
+        _ => {
+            let (_, diff_path) = get_diff_paths(paths)?;
+            let manager = GlobalManager::from(cli.global_manager()).ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("{} is not a supported package manager", cli.global_manager()),
+                )
+            })?;
+            (
+                global_prefix::get_global_root(&manager)?.node_modules,
+                global_prefix::get_diff_root(diff_path, &installations)?,
+            )
+        }
+    };
+
+    let mut globals = Vec::<GlobalModule>::new();
+    collect_dependencies(&roots.0, &mut globals, None)?;
+    let mut diff_globals = Vec::<GlobalModule>::new();
+    collect_dependencies(&roots.1, &mut diff_globals, None)?;
+
+    let pairs = GlobalDiffedPair::get_pairs(&globals, &diff_globals);
+    print_table(&pairs);
+    print_completion_message(format!(
+        "\n{} of {} global packages differ between {} and {}.\n",
+        pairs.iter().filter(|pair| pair.is_different()).count(),
+        pairs.len(),
+        roots.0.display(),
+        roots.1.display(),
+    ))?;
+    Ok(())
+}
+
+pub fn run_matrix(projects: &[PathBuf], inconsistent: bool) -> Result<(), Error> {
+    if projects.len() < 2 {
+        return Err(Error::other("matrix needs at least two projects to compare"));
+    }
+
+    let mut titles = vec![Cell::new("package")];
+    let mut dependencies = Vec::new();
+    for project in projects {
+        titles.push(Cell::new(&PjsonDetail::from(project)?.name));
+        dependencies.push(get_diff_deps(project)?);
+    }
+    titles.push(Cell::new("consistency"));
+
+    let rows: Vec<DiffedRow> = DiffedRow::get_rows(&dependencies)
+        .into_iter()
+        .filter(|row| !inconsistent || !row.is_consistent())
+        .collect();
+    print_titled_table(Row::new(titles), &rows);
+    print_completion_message(format!(
+        "\n{} of {} packages have different versions across {} projects.\n",
+        rows.iter().filter(|row| !row.is_consistent()).count(),
+        rows.len(),
+        projects.len(),
+    ))?;
+    Ok(())
+}
+
+pub fn run_scan(root: &Path, queries: &[String], format: &str, output: Option<&Path>) -> Result<(), Error> {
+    let queries = queries
+        .iter()
+        .map(|query| Query::parse(query))
+        .collect::<Result<Vec<Query>, Error>>()?;
+    let filter_re = Regex::new(&Cli::get().filter).unwrap();
+
+    let projects = fleet::find_projects(root);
+    let mut matches = Vec::new();
+    for project in projects.iter() {
+        let app_pjson = match PjsonDetail::from(project) {
+            Ok(app_pjson) => app_pjson,
+            Err(err) => {
+                eprintln!("Failed to read {}: {}", project.join("package.json").display(), err);
+                continue;
+            }
+        };
+        let dependencies = match get_node_modules_path(project).is_dir() {
+            true => match get_diff_deps(project) {
+                Ok(dependencies) => dependencies,
+                Err(err) => {
+                    eprintln!("Failed to read {}: {}", get_node_modules_path(project).display(), err);
+                    continue;
+                }
+            },
+            false => {
+                let mut dependencies = match Lockfile::from(project) {
+                    Ok(Some(lockfile)) => node_module::get_locked_deps(Some(&app_pjson), &lockfile),
+                    Ok(None) => fleet::get_declared_deps(&app_pjson),
+                    Err(err) => {
+                        eprintln!("Failed to read the lockfile in {}: {}", project.display(), err);
+                        continue;
+                    }
+                };
+                dependencies.retain(|dependency| {
+                    filter_re.is_match(&dependency.name) && node_module::standard_filter(&dependency.dep_type)
+                });
+                dependencies
+            }
+        };
+        matches.extend(
+            dependencies
+                .iter()
+                .filter(|dependency| queries.is_empty() || queries.iter().any(|query| query.matches(dependency)))
+                .map(|dependency| FleetMatch::from(&app_pjson.name, project, dependency)),
+        );
+    }
+
+    let document = match format {
+        "json" => serde_json::to_string_pretty(&matches)? + "\n",
+        "csv" => fleet::to_csv(&matches),
+        _ => {
+            let groups: Vec<&[FleetMatch]> = matches.chunk_by(|a, b| a.path == b.path).collect();
+            for group in groups.iter() {
+                print_completion_message(format!("\n{} ({})\n", group[0].project, group[0].path))?;
+                print_table(group);
+            }
+            print_completion_message(format!(
+                "\n{} matches in {} of {} projects under {}.\n",
+                matches.len(),
+                groups.len(),
+                projects.len(),
+                root.display(),
+            ))?;
+            return Ok(());
+        }
+    };
+
+    match output {
+        Some(path) => {
+            fs::write(path, document)?;
+            print_completion_message(format!("\nWrote {} matches to {}.\n", matches.len(), path.display()))?;
+        }
+        None => print_completion_message(document)?,
+    }
+    Ok(())
+}
+
+pub fn run_snapshot_save(file: &Path) -> Result<(), Error> {
+    let cli = Cli::get();
+    let app_pjson = PjsonDetail::from(&cli.path)?;
+    let dependencies = get_standard_deps(&app_pjson)?;
+    let lockfile = Lockfile::from(&cli.path)?;
+    Snapshot::from(&app_pjson, &cli.path, &dependencies, lockfile.as_ref()).write(file)?;
+    print_completion_message(format!(
+        "\nRecorded {} packages of {} in {}.\n",
+        dependencies.len(),
+        app_pjson.name,
+        file.display()
+    ))?;
+    Ok(())
+}
+
+pub fn run_snapshot_diff(file: &Path) -> Result<(), Error> {
+    let cli = Cli::get();
+    let snapshot = Snapshot::read(file)?;
+    let baseline = snapshot.modules(&cli.path);
+    let app_pjson = PjsonDetail::from(&cli.path)?;
+    let dependencies = get_standard_deps(&app_pjson)?;
+
+    let changed: Vec<DiffedPair> = DiffedPair::get_pairs(&baseline, &dependencies)
+        .into_iter()
+        .filter(|pair| pair.version.0 != pair.version.1)
+        .collect();
+    print_table(&changed);
+
+    for (name, recorded, current) in snapshot.changed_sources(&dependencies) {
+        print_completion_message(format!("\n{} was installed from {} and is now from {}", name, recorded, current))?;
+    }
+    let count = |added: bool, removed: bool| {
+        changed
+            .iter()
+            .filter(|pair| pair.version.0.is_none() == added && pair.version.1.is_none() == removed)
+            .count()
+    };
+    print_completion_message(format!(
+        "\n{} packages changed version, {} were added and {} were removed since {}.\n",
+        count(false, false),
+        count(true, false),
+        count(false, true),
+        file.display(),
+    ))?;
+    Ok(())
+}
+
+/// Returns the two sides of `--diff`, where the first is `--path` unless two are given.
+fn get_diff_paths(paths: &[PathBuf]) -> Result<(&Path, &Path), Error> {
+    match paths {
+        [diff_path] => Ok((&Cli::get().path, diff_path)),
+        [path, diff_path] => Ok((path, diff_path)),
+        _ => Err(Error::other("--diff takes one or two paths")),
+    }
+}
+
+fn get_diff_deps(path: &Path) -> Result<Vec<StandardModule>, Error> {
+    let pjson = PjsonDetail::from(path)?;
+    get_project_deps(path, &pjson)
+}
+
+pub fn run_outdated(
+    concurrency: usize,
+    timeout: u64,
+    offline: bool,
+    cache: Option<&Path>,
+) -> Result<(), Error> {
+    let cli = Cli::get();
+    let app_pjson = PjsonDetail::from(&cli.path)?;
+    let dependencies = get_standard_deps(&app_pjson)?;
+    let npmrc = Npmrc::from(&cli.path)?;
+
+    let names = node_module::get_registry_names(&dependencies);
+    let packuments = match offline {
+        true => NpmCache::from(&npmrc, cache).packuments(names),
+        false => RegistryClient::new(&npmrc, Duration::from_secs(timeout)).fetch_all(names, concurrency),
+    };
+
+    let outdated: Vec<OutdatedModule> = dependencies
+        .iter()
+        .map(|dependency| OutdatedModule::from(dependency, &packuments))
+        .collect();
+    print_table(&outdated);
+
+    for module in outdated.iter() {
+        if let Some(err) = &module.error {
+            eprintln!("Failed to fetch {}: {}", module.module.name, err);
+        }
+    }
+    Ok(())
+}
+
+pub fn run_freshness(concurrency: usize, timeout: u64, packuments: Option<&Path>) -> Result<(), Error> {
+    let cli = Cli::get();
+    let app_pjson = PjsonDetail::from(&cli.path)?;
+    let dependencies = get_standard_deps(&app_pjson)?;
+    let npmrc = Npmrc::from(&cli.path)?;
+
+    let names = node_module::get_registry_names(&dependencies);
+    let packuments = match packuments {
+        Some(dir) => registry::read_packuments(dir, names),
+        None => RegistryClient::new(&npmrc, Duration::from_secs(timeout)).fetch_all(names, concurrency),
+    };
+
+    let fresh: Vec<FreshnessModule> = dependencies
+        .iter()
+        .map(|dependency| FreshnessModule::from(dependency, &packuments))
+        .collect();
+    print_table(&fresh);
+
+    for module in fresh.iter() {
+        if let Some(err) = &module.error {
+            eprintln!("Failed to fetch {}: {}", module.module.name, err);
+        }
+    }
+
+    let measured: Vec<&Freshness> = fresh.iter().filter_map(|module| module.freshness.as_ref()).collect();
+    let libyears: f64 = measured.iter().map(|freshness| freshness.libyears()).sum();
+    print_completion_message(format!(
+        "\nTotal libyear: {:.2} across {} packages in version {} of {}.\n",
+        libyears,
+        measured.len(),
+        app_pjson.version,
+        app_pjson.name,
+    ))?;
+    Ok(())
+}
+
+pub fn run_licenses(policy: Option<&Path>) -> Result<(), Error> {
+    let cli = Cli::get();
+    let app_pjson = PjsonDetail::from(&cli.path)?;
+    let dependencies = get_installed_tree(&app_pjson)?;
+    let lockfile = Lockfile::from(&cli.path)?;
+    let policy = match policy {
+        Some(path) => Some(LicensePolicy::from(path)?),
+        None => None,
+    };
+
+    print_table(&licenses::LicenseGroup::get_groups(&dependencies));
+
+    let findings: Vec<licenses::LicenseFinding> = dependencies
+        .iter()
+        .filter_map(|dependency| {
+            let production = node_module::is_production(dependency, lockfile.as_ref());
+            licenses::LicenseFinding::from(dependency, policy.as_ref(), production)
+        })
+        .collect();
+    if !findings.is_empty() {
+        print_completion_message(String::from("\nPackages with missing, unknown or non-permitted licenses:\n"))?;
+        print_table(&findings);
+    }
+
+    let violations = findings
+        .iter()
+        .filter(|finding| finding.production && finding.issue == licenses::LicenseIssue::Violation)
+        .count();
+    if violations > 0 {
+        return Err(Error::other(format!(
+            "{} production dependencies have licenses that are not permitted",
+            violations
+        )));
+    }
+    Ok(())
+}
+
+pub fn run_notices(output: Option<&Path>, markdown: bool) -> Result<(), Error> {
+    let cli = Cli::get();
+    let app_pjson = PjsonDetail::from(&cli.path)?;
+    let dependencies = get_installed_tree(&app_pjson)?;
+    let lockfile = Lockfile::from(&cli.path)?;
+
+    let production: Vec<&StandardModule> = dependencies
+        .iter()
+        .filter(|dependency| node_module::is_production(dependency, lockfile.as_ref()))
+        .collect();
+    let notices = Notices::from(&production)?;
+    let document = match markdown {
+        true => notices.to_markdown(),
+        false => notices.to_text(),
+    };
+
+    match output {
+        Some(path) => {
+            fs::write(path, document)?;
+            print_completion_message(format!(
+                "\nWrote notices for {} packages to {}.\n",
+                production.len(),
+                path.display()
+            ))?;
+        }
+        None => print_completion_message(document)?,
+    }
+    Ok(())
+}
+
+pub fn run_advisories(database: &Path) -> Result<(), Error> {
+    let cli = Cli::get();
+    let database = AdvisoryDatabase::from(database)?;
+
+    let packages = match Lockfile::from(&cli.path)? {
+        Some(lockfile) => {
+            let app_pjson = PjsonDetail::from(&cli.path)?;
+            let roots: Vec<String> = app_pjson
+                .dependencies
+                .iter()
+                .chain(app_pjson.dev_dependencies.iter())
+                .flat_map(|dependencies| dependencies.keys().cloned())
+                .collect();
+            InstalledPackage::from_lockfile(&lockfile, &roots)
+        }
+        None => {
+            let app_pjson = PjsonDetail::from(&cli.path)?;
+            InstalledPackage::from_modules(&get_standard_deps(&app_pjson)?)
+        }
+    };
+
+    let filter_re = Regex::new(&cli.filter).unwrap();
+    let mut findings = advisories::audit(packages, &database);
+    findings.retain(|finding| filter_re.is_match(&finding.package.name));
+
+    print_table(&findings);
+    if !findings.is_empty() {
+        return Err(Error::other(format!(
+            "{} advisories affect the installed packages",
+            findings.len()
+        )));
+    }
+    print_completion_message(String::from("\nNo advisories affect the installed packages.\n"))?;
+    Ok(())
+}
+
+pub fn run_size(packages: bool, sort: &str) -> Result<(), Error> {
+    let cli = Cli::get();
+    let app_pjson = PjsonDetail::from(&cli.path)?;
+    let usage = DiskUsage::from(&cli.path)?;
+    let filter_re = Regex::new(&cli.filter).unwrap();
+
+    let total_bytes: u64 = usage.packages.values().map(|package| package.bytes).sum();
+    let total_files: u64 = usage.packages.values().map(|package| package.files).sum();
+    let total_packages = usage.packages.len();
+
+    if packages {
+        let mut sizes: Vec<PackageSize> = usage
+            .packages
+            .into_values()
+            .filter(|package| filter_re.is_match(&package.name))
+            .collect();
+        match sort {
+            "name" => sizes.sort_by(|a, b| a.install_path.cmp(&b.install_path)),
+            _ => sizes.sort_by(|a, b| b.bytes.cmp(&a.bytes).then(a.install_path.cmp(&b.install_path))),
+        }
+        print_table(&sizes);
+    } else {
+        let roots: Vec<String> = app_pjson
+            .dependencies
+            .iter()
+            .chain(app_pjson.dev_dependencies.iter())
+            .flat_map(|dependencies| dependencies.keys().cloned())
+            .collect();
+        let mut subtrees = usage.get_subtrees(&roots);
+        subtrees.retain(|subtree| {
+            filter_re.is_match(&subtree.name)
+                && node_module::standard_filter(&node_module::get_dep_type(&subtree.name, &app_pjson))
+        });
+        match sort {
+            "name" => subtrees.sort_by(|a, b| a.name.cmp(&b.name)),
+            _ => subtrees.sort_by(|a, b| b.inclusive_bytes.cmp(&a.inclusive_bytes).then(a.name.cmp(&b.name))),
+        }
+        print_table(&subtrees);
+    }
+
+    print_completion_message(format!(
+        "\n{} in {} files across {} installed packages.\n",
+        disk_usage::format_bytes(total_bytes),
+        total_files,
+        total_packages,
+    ))?;
+    Ok(())
+}
+
+pub fn run_engines(target_node: Option<&str>, target_npm: Option<&str>) -> Result<(), Error> {
+    let cli = Cli::get();
+    let app_pjson = PjsonDetail::from(&cli.path)?;
+    let dependencies = get_standard_deps(&app_pjson)?;
+
+    let get_target = |engine: &str, target: Option<&str>| match target {
+        Some(target) => engines::parse_target_version(target)
+            .map(Some)
+            .ok_or_else(|| Error::other(format!("{} is not a valid {} version", target, engine))),
+        None => Ok(engines::get_binary_version(engine)),
+    };
+    let node = get_target("node", target_node)?;
+    let npm = get_target("npm", target_npm)?;
+
+    let mut targets = Vec::new();
+    if let Some(node) = &node {
+        targets.push(("node", node));
+    }
+    if let Some(npm) = &npm {
+        targets.push(("npm", npm));
+    }
+    if targets.is_empty() {
+        return Err(Error::other("could not find node or npm on PATH; use --target-node"));
+    }
+
+    let findings: Vec<EngineFinding> = dependencies
+        .iter()
+        .flat_map(|dependency| EngineFinding::from(dependency, &targets))
+        .collect();
+    print_table(&findings);
+
+    let incompatible = findings
+        .iter()
+        .filter(|finding| finding.issue == engines::EngineIssue::Incompatible)
+        .count();
+    if incompatible > 0 {
+        return Err(Error::other(format!(
+            "{} packages do not support the target engines",
+            incompatible
+        )));
+    }
+
+    let targets: Vec<String> = targets
+        .iter()
+        .map(|(engine, version)| format!("{} {}", engine, version))
+        .collect();
+    print_completion_message(format!(
+        "\nAll {} packages support {}.\n",
+        dependencies.len(),
+        targets.join(" and "),
+    ))?;
+    Ok(())
+}
+
+pub fn run_toolchain() -> Result<(), Error> {
+    let cli = Cli::get();
+    let app_pjson = PjsonDetail::from(&cli.path)?;
+    let requirements = Requirement::from(&cli.path, &app_pjson)?;
+    if requirements.is_empty() {
+        print_completion_message(String::from("\nNo Node or package manager versions are pinned.\n"))?;
+        return Ok(());
+    }
+
+    let mut active = HashMap::new();
+    for requirement in requirements.iter() {
+        if !active.contains_key(&requirement.tool) {
+            active.insert(requirement.tool.clone(), engines::get_binary_version(&requirement.tool));
+        }
+    }
+
+    let checks: Vec<ToolchainCheck> = requirements
+        .iter()
+        .map(|requirement| ToolchainCheck::from(requirement, &active))
+        .collect();
+    print_table(&checks);
+
+    let conflicts = toolchain::get_conflicts(&requirements);
+    if !conflicts.is_empty() {
+        print_completion_message(String::from("\nRequirements that cannot all be met:\n"))?;
+        print_table(&conflicts);
+    }
+
+    let mismatches = checks
+        .iter()
+        .filter(|check| check.status == toolchain::ToolchainStatus::Mismatch)
+        .count();
+    if mismatches > 0 || !conflicts.is_empty() {
+        return Err(Error::other(format!(
+            "{} requirements are not met by the tools on PATH and {} pairs of requirements conflict",
+            mismatches,
+            conflicts.len()
+        )));
+    }
+    Ok(())
+}
+
+pub fn run_registry_audit() -> Result<(), Error> {
+    let cli = Cli::get();
+    let npmrc = Npmrc::from(&cli.path)?;
+    let internal_packages = match &cli.internal_packages {
+        Some(path) => registry_audit::read_internal_packages(path)?,
+        None => Vec::new(),
+    };
+
+    let packages = match Lockfile::from(&cli.path)? {
+        Some(lockfile) => ResolvedPackage::from_lockfile(&lockfile),
+        None => {
+            let app_pjson = PjsonDetail::from(&cli.path)?;
+            ResolvedPackage::from_modules(&get_standard_deps(&app_pjson)?)
+        }
+    };
+
+    let filter_re = Regex::new(&cli.filter).unwrap();
+    let mut findings = registry_audit::audit(packages, &npmrc, &internal_packages, &cli.approved_registries);
+    findings.retain(|finding| filter_re.is_match(&finding.name));
+
+    print_table(&findings);
+    if !findings.is_empty() {
+        return Err(Error::other(format!(
+            "{} packages were resolved from unexpected registries or could not be checked",
+            findings.len()
+        )));
+    }
+    print_completion_message(String::from("\nAll packages were resolved from their expected registries.\n"))?;
+    Ok(())
+}
+
 fn get_standard_deps(app_pjson: &PjsonDetail) -> Result<Vec<StandardModule>, Error> {
-    let base_path = get_node_modules_path(&Cli::get().path);
+    get_project_deps(&Cli::get().path, app_pjson)
+}
+
+/// Returns the installed packages and the copies nested in their node_modules folders, which are
+/// filtered the same way.
+fn get_installed_tree(app_pjson: &PjsonDetail) -> Result<Vec<StandardModule>, Error> {
+    let cli = Cli::get();
+    let mut dependencies = get_standard_deps(app_pjson)?;
+    let filter_re = Regex::new(&cli.filter).unwrap();
+    let nested = node_module::get_nested_deps(&get_node_modules_path(&cli.path))?;
+    dependencies.extend(
+        nested
+            .into_iter()
+            .filter(|dependency| dependency.filter_by_regex(&filter_re) && dependency.filter_by_args()),
+    );
+    Ok(dependencies)
+}
+
+/// Returns the packages installed in the project at `path`, with the resolved URL from its
+/// lockfile for any that don't record one.
+fn get_project_deps(path: &Path, app_pjson: &PjsonDetail) -> Result<Vec<StandardModule>, Error> {
     let mut dependencies = Vec::<StandardModule>::new();
-    collect_dependencies(&base_path, &mut dependencies, Some(&app_pjson))?;
+    collect_dependencies(&get_node_modules_path(path), &mut dependencies, Some(app_pjson))?;
+
+    if let Some(lockfile) = Lockfile::from(path)? {
+        for dependency in dependencies.iter_mut() {
+            if dependency.resolved.is_none() {
+                dependency.resolved = lockfile
+                    .get(&dependency.name)
+                    .and_then(|locked| locked.resolved.clone());
+            }
+        }
+    }
+
     Ok(dependencies)
 }
 
-fn get_global_path() -> PathBuf {
-    let mut node_path = which("node").unwrap();
-    node_path.pop();
-    node_path.pop();
-    node_path.push("lib");
-    node_path
+/// Fills in the newest version in the npm cache for each dependency installed from a registry.
+fn fill_cached_versions(dependencies: &mut [StandardModule]) -> Result<(), Error> {
+    let npmrc = Npmrc::from(&Cli::get().path)?;
+    let names = node_module::get_registry_names(dependencies);
+    let newest = NpmCache::from(&npmrc, None).newest_versions(&names)?;
+    for dependency in dependencies.iter_mut() {
+        if node_module::is_from_registry(dependency) {
+            dependency.cached = newest.get(node_module::get_package_name(dependency)).cloned();
+        }
+    }
+    Ok(())
+}
+
+/// Keeps the dependencies whose source matches the --source pattern, if there is one.
+fn filter_by_source(dependencies: &mut Vec<StandardModule>) {
+    if let Some(source) = &Cli::get().source {
+        let source_re = Regex::new(source).unwrap();
+        dependencies.retain(|dependency| source_re.is_match(&dependency.source().to_string()));
+    }
 }
 
-fn get_node_modules_path(path: &PathBuf) -> PathBuf {
+fn get_node_modules_path(path: &Path) -> PathBuf {
     let mut base_path = PathBuf::from(path);
     base_path.push("node_modules");
     base_path
 }
 
-fn collect_dependencies<T: node_module::NodeModule + Default>(
-    base_path: &PathBuf,
+fn collect_dependencies<T: NodeModule + Default>(
+    base_path: &Path,
     dependencies: &mut Vec<T>,
     app_pjson: Option<&PjsonDetail>,
 ) -> Result<(), Error> {
@@ -82,36 +1053,34 @@ fn collect_dependencies<T: node_module::NodeModule + Default>(
 
     let filter_re = Regex::new(&Cli::get().filter).unwrap();
 
-    for entry in node_modules {
-        if let Ok(entry) = entry {
-            let folder_name = entry.file_name().into_string().unwrap();
+    for entry in node_modules.flatten() {
+        let folder_name = entry.file_name().into_string().unwrap();
 
-            if folder_name.starts_with('.') {
-                continue;
-            }
-            let mut dep_path = base_path.clone();
-            dep_path.push(&folder_name);
-
-            if folder_name.starts_with('@') {
-                collect_dependencies(&dep_path, dependencies, app_pjson)?;
-            } else {
-                let mut detail: T = Default::default();
-                detail.populate(&dep_path, app_pjson)?;
-                if detail.filter_by_regex(&filter_re) && detail.filter_by_args() {
-                    dependencies.push(detail)
-                }
+        if folder_name.starts_with('.') {
+            continue;
+        }
+        let mut dep_path = base_path.to_path_buf();
+        dep_path.push(&folder_name);
+
+        if folder_name.starts_with('@') {
+            collect_dependencies(&dep_path, dependencies, app_pjson)?;
+        } else {
+            let mut detail: T = Default::default();
+            detail.populate(&dep_path, app_pjson)?;
+            if detail.filter_by_regex(&filter_re) && detail.filter_by_args() {
+                dependencies.push(detail)
             }
         }
     }
 
-    dependencies.sort_by(|a, b| a.order(&b));
+    dependencies.sort_by(|a, b| a.order(b));
 
     Ok(())
 }
 
-fn print_table<T: node_module::PrintTable>(dependencies: &Vec<T>) {
+fn print_table<T: node_module::PrintTable>(dependencies: &[T]) {
     let mut table = Table::new();
-    if dependencies.len() == 0 {
+    if dependencies.is_empty() {
         return;
     }
 
@@ -121,6 +1090,19 @@ fn print_table<T: node_module::PrintTable>(dependencies: &Vec<T>) {
     table.printstd();
 }
 
+fn print_titled_table<T: node_module::PrintTable>(titles: Row, rows: &[T]) {
+    if rows.is_empty() {
+        return;
+    }
+
+    let mut table = Table::new();
+    table.set_titles(titles);
+    for row in rows {
+        table.add_row(row.table_row());
+    }
+    table.printstd();
+}
+
 fn print_completion_message(message: String) -> Result<(), Error> {
     let stdout = io::stdout();
     let mut handle = stdout.lock();
@@ -0,0 +1,129 @@
+use std::collections::BTreeSet;
+
+use prettytable::{color, Attr};
+
+use crate::node_module::*;
+use crate::node_module::standard_module::StandardModule;
+use crate::semver::Semver;
+
+/// The versions of a package across several projects, in the order the projects were given.
+pub struct DiffedRow<'a> {
+    pub name: &'a str,
+    pub versions: Vec<Option<&'a Semver>>,
+}
+
+impl<'a> DiffedRow<'a> {
+    /// Returns the DiffedRow type for each package installed in any of the projects, sorted by
+    /// name.
+    pub fn get_rows(projects: &'a [Vec<StandardModule>]) -> Vec<Self> {
+        let names: BTreeSet<&str> = projects
+            .iter()
+            .flatten()
+            .map(|dependency| dependency.name.as_str())
+            .collect();
+
+        names
+            .into_iter()
+            .map(|name| DiffedRow {
+                name,
+                versions: projects
+                    .iter()
+                    .map(|dependencies| {
+                        dependencies
+                            .iter()
+                            .find(|dependency| dependency.name == name)
+                            .and_then(|dependency| dependency.version.as_ref())
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Returns each version found, oldest first.
+    pub fn distinct_versions(&self) -> Vec<&'a Semver> {
+        let mut versions: Vec<&Semver> = self.versions.iter().flatten().copied().collect();
+        versions.sort();
+        versions.dedup();
+        versions
+    }
+
+    /// Returns true if every project that has the package has the same version of it.
+    pub fn is_consistent(&self) -> bool {
+        self.distinct_versions().len() <= 1
+    }
+}
+
+impl<'a> PrintTable for DiffedRow<'a> {
+    fn table_row(&self) -> Row {
+        let distinct = self.distinct_versions();
+        let (oldest, newest) = (distinct.first(), distinct.last());
+
+        let mut cells = vec![new_cell(self.name)];
+        for version in self.versions.iter() {
+            let cell = match version {
+                Some(version) => new_cell(&version.to_string()),
+                None => new_cell(""),
+            };
+            cells.push(match version {
+                Some(version) if distinct.len() > 1 && Some(version) == newest => {
+                    cell.with_style(Attr::ForegroundColor(color::GREEN))
+                }
+                Some(version) if distinct.len() > 1 && Some(version) == oldest => {
+                    cell.with_style(Attr::ForegroundColor(color::RED))
+                }
+                _ => cell,
+            });
+        }
+
+        let found = self.versions.iter().filter(|version| version.is_some()).count();
+        let mut consistency = match distinct.len() {
+            0 => String::from("no versions"),
+            1 => String::from("consistent"),
+            count => format!("{} versions", count),
+        };
+        if found < self.versions.len() {
+            consistency = format!("{}, in {} of {}", consistency, found, self.versions.len());
+        }
+        cells.push(match self.is_consistent() {
+            true => new_cell(&consistency).with_style(Attr::ForegroundColor(color::GREEN)),
+            false => new_cell(&consistency).with_style(Attr::ForegroundColor(color::RED)),
+        });
+        Row::new(cells)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(dependencies: &[(&str, &str)]) -> Vec<StandardModule> {
+        dependencies
+            .iter()
+            .map(|(name, version)| StandardModule {
+                name: name.to_string(),
+                version: Semver::from(version.to_string()),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn compares_many_projects() {
+        let projects = vec![
+            project(&[("lodash", "4.17.21"), ("react", "18.2.0")]),
+            project(&[("lodash", "4.17.15"), ("react", "18.2.0")]),
+            project(&[("lodash", "4.17.20")]),
+        ];
+
+        let rows = DiffedRow::get_rows(&projects);
+        let names: Vec<&str> = rows.iter().map(|row| row.name).collect();
+        assert_eq!(names, vec!["lodash", "react"]);
+
+        let lodash: Vec<String> = rows[0].distinct_versions().iter().map(|version| version.to_string()).collect();
+        assert_eq!(lodash, vec!["4.17.15", "4.17.20", "4.17.21"]);
+        assert!(!rows[0].is_consistent());
+
+        assert!(rows[1].is_consistent());
+        assert!(rows[1].versions[2].is_none());
+    }
+}
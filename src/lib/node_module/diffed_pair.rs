@@ -19,12 +19,12 @@ impl<'a> DiffedPair<'a>{
         }
     }
 
-    pub fn get_pairs(dependencies: &'a Vec<StandardModule>, diff_dependencies: &'a Vec<StandardModule>) -> Vec<Self> {
+    pub fn get_pairs(dependencies: &'a [StandardModule], diff_dependencies: &'a [StandardModule]) -> Vec<Self> {
         let mut diffed_pairs = Vec::new();
         let mut found_deps = Vec::new();
 
         for dependency in dependencies {
-            let mut new_pair = DiffedPair::from(&dependency);
+            let mut new_pair = DiffedPair::from(dependency);
 
             for diff_dependency in diff_dependencies.iter() {
                 if dependency.name == diff_dependency.name {
@@ -50,25 +50,25 @@ impl<'a> DiffedPair<'a>{
             }
         }
 
-        diffed_pairs.sort_by(|a, b| a.name.cmp(&b.name));
+        diffed_pairs.sort_by(|a, b| a.name.cmp(b.name));
         diffed_pairs
     }
 }
 
 impl<'a> PrintTable for DiffedPair<'a> {
     fn table_row(&self) -> Row {
-        let (version_one, version_two) = diffed_cells(&self.version.0, &self.version.1);
+        let (version_one, version_two) = diffed_cells(self.version.0, self.version.1);
         Row::new(vec![
-            new_cell(&self.name),
-            get_pjson_version_cell(&self.dep_type.0),
+            new_cell(self.name),
+            get_pjson_version_cell(self.dep_type.0),
             version_one,
-            get_pjson_version_cell(&self.dep_type.1),
+            get_pjson_version_cell(self.dep_type.1),
             version_two,
         ])
    }
 }
 
-fn diffed_cells(version_one: &Option<Semver>, version_two: &Option<Semver>) -> (Cell, Cell) {
+pub fn diffed_cells(version_one: &Option<Semver>, version_two: &Option<Semver>) -> (Cell, Cell) {
 
     fn cell(version: &Option<Semver>) -> Cell {
         let cell_contents = match &version {
@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+use std::io::Error;
+
+use crate::freshness::Freshness;
+use crate::node_module::*;
+use crate::node_module::diffed_pair::diffed_cells;
+use crate::node_module::standard_module::StandardModule;
+use crate::registry::Packument;
+use crate::semver::Semver;
+
+pub struct FreshnessModule<'a> {
+    pub module: &'a StandardModule,
+    pub latest: Option<Semver>,
+    pub freshness: Option<Freshness>,
+    pub error: Option<String>,
+}
+
+impl<'a> FreshnessModule<'a> {
+    pub fn from(
+        module: &'a StandardModule,
+        packuments: &HashMap<String, Result<Packument, Error>>,
+    ) -> Self {
+        let mut fresh = FreshnessModule {
+            module,
+            latest: None,
+            freshness: None,
+            error: None,
+        };
+
+        match packuments.get(get_package_name(module)) {
+            Some(Ok(packument)) => {
+                fresh.latest = packument.latest();
+                fresh.freshness = module
+                    .version
+                    .as_ref()
+                    .and_then(|version| Freshness::from(version, packument));
+            }
+            Some(Err(err)) => fresh.error = Some(err.to_string()),
+            None => {}
+        }
+
+        fresh
+    }
+}
+
+impl<'a> PrintTable for FreshnessModule<'a> {
+    fn table_row(&self) -> Row {
+        let (current, latest) = diffed_cells(&self.module.version, &self.latest);
+        let (days, libyears, releases) = match &self.freshness {
+            Some(freshness) => (
+                format!("{} days", freshness.days),
+                format!("{:.2}", freshness.libyears()),
+                format!("{}/{}/{}", freshness.major, freshness.minor, freshness.patch),
+            ),
+            None => (String::new(), String::new(), String::new()),
+        };
+
+        Row::new(vec![
+            new_cell(&self.module.name),
+            get_pjson_version_cell(&self.module.dep_type),
+            current,
+            latest,
+            new_cell(&days),
+            new_cell(&libyears),
+            new_cell(&releases),
+        ])
+    }
+}
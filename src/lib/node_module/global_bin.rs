@@ -0,0 +1,201 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use prettytable::{color, Attr};
+use serde_json::Value;
+use which::which;
+
+use crate::node_module::*;
+use crate::node_module::global_module::GlobalModule;
+
+/// Returns the executables from the `bin` field, which is either one path named after the
+/// package or an object of names and paths.
+pub fn get_bins(bin: Option<&Value>, package_name: &str) -> Vec<(String, String)> {
+    let mut bins: Vec<(String, String)> = match bin {
+        Some(Value::String(path)) => {
+            let name = package_name.rsplit('/').next().unwrap_or(package_name);
+            vec![(name.to_string(), path.clone())]
+        }
+        Some(Value::Object(object)) => object
+            .iter()
+            .filter_map(|(name, path)| Some((name.clone(), path.as_str()?.to_string())))
+            .collect(),
+        _ => Vec::new(),
+    };
+    bins.sort();
+    bins
+}
+
+#[derive(Debug, PartialEq)]
+pub enum BinLink {
+    /// A symlink to the package's executable.
+    Linked,
+    /// A script that runs the package's executable, as pnpm and npm on Windows create.
+    Shim,
+    /// A symlink or file for a different package.
+    Elsewhere(PathBuf),
+    Dangling,
+    Missing,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum BinPath {
+    /// Running the name runs this package's executable.
+    First,
+    /// Another executable with the same name comes earlier on PATH.
+    Shadowed(PathBuf),
+    /// The global bin folder is not on PATH.
+    NotOnPath,
+}
+
+/// An executable of a global package, how it is linked into the global bin folder and whether
+/// it is what runs from PATH.
+pub struct GlobalBin<'a> {
+    pub module: &'a GlobalModule,
+    pub name: String,
+    pub link: BinLink,
+    pub path: BinPath,
+}
+
+impl<'a> GlobalBin<'a> {
+    /// Returns the GlobalBin type for each executable of the package.
+    pub fn from(module: &'a GlobalModule, bin_dir: &Path) -> Vec<Self> {
+        module
+            .bins
+            .iter()
+            .map(|(name, target)| {
+                let link = get_bin_link(&bin_dir.join(name), &module.path.join(target), &module.path);
+                let path = match which(name) {
+                    Ok(found) if is_same_file(found.parent(), Some(bin_dir)) => BinPath::First,
+                    Ok(found) => BinPath::Shadowed(found),
+                    Err(_) => BinPath::NotOnPath,
+                };
+                GlobalBin {
+                    module,
+                    name: name.clone(),
+                    link,
+                    path,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Returns how the executable in the global bin folder relates to the package's executable.
+pub fn get_bin_link(link: &Path, target: &Path, package: &Path) -> BinLink {
+    let metadata = match link.symlink_metadata() {
+        Ok(metadata) => metadata,
+        Err(_) => return BinLink::Missing,
+    };
+
+    if metadata.file_type().is_symlink() {
+        return match fs::canonicalize(link) {
+            Ok(resolved) if is_same_file(Some(&resolved), Some(target)) => BinLink::Linked,
+            Ok(resolved) => BinLink::Elsewhere(resolved),
+            Err(_) => BinLink::Dangling,
+        };
+    }
+
+    // Shims name the package folder somewhere in their script, by its full or relative path.
+    let contents = String::from_utf8_lossy(&fs::read(link).unwrap_or_default()).to_string();
+    let package = fs::canonicalize(package).unwrap_or_else(|_| package.to_path_buf());
+    let mentions = |path: &Path| contents.contains(&format!("{}/", path.to_string_lossy()));
+    match mentions(&package) || mentions(&get_package_suffix(&package)) {
+        true => BinLink::Shim,
+        false => BinLink::Elsewhere(link.to_path_buf()),
+    }
+}
+
+/// Returns the end of a package path from its node_modules folder, e.g. `node_modules/a`, which
+/// shims written with relative paths contain.
+fn get_package_suffix(package: &Path) -> PathBuf {
+    let mut components = Vec::new();
+    for component in package.iter().rev() {
+        components.push(component);
+        if component == "node_modules" {
+            break;
+        }
+    }
+    components.iter().rev().collect()
+}
+
+fn is_same_file(a: Option<&Path>, b: Option<&Path>) -> bool {
+    match (a.map(fs::canonicalize), b.map(fs::canonicalize)) {
+        (Some(Ok(a)), Some(Ok(b))) => a == b,
+        _ => false,
+    }
+}
+
+impl<'a> PrintTable for GlobalBin<'a> {
+    fn table_row(&self) -> Row {
+        let (link, link_colour) = match &self.link {
+            BinLink::Linked => (String::from("linked"), color::GREEN),
+            BinLink::Shim => (String::from("shim"), color::GREEN),
+            BinLink::Elsewhere(path) => (format!("points to {}", path.display()), color::RED),
+            BinLink::Dangling => (String::from("dangling"), color::RED),
+            BinLink::Missing => (String::from("missing"), color::RED),
+        };
+        let (path, path_colour) = match &self.path {
+            BinPath::First => (String::from("first on PATH"), color::GREEN),
+            BinPath::Shadowed(found) => (format!("shadowed by {}", found.display()), color::YELLOW),
+            BinPath::NotOnPath => (String::from("not on PATH"), color::YELLOW),
+        };
+        Row::new(vec![
+            new_cell(&self.module.name),
+            new_cell(&self.name),
+            new_cell(&link).with_style(Attr::ForegroundColor(link_colour)),
+            new_cell(&path).with_style(Attr::ForegroundColor(path_colour)),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    #[test]
+    fn reads_bin_fields() {
+        let path = serde_json::json!("./bin/cli.js");
+        assert_eq!(
+            get_bins(Some(&path), "@s/tool"),
+            vec![(String::from("tool"), String::from("./bin/cli.js"))]
+        );
+
+        let object = serde_json::json!({ "tsserver": "./bin/tsserver", "tsc": "./bin/tsc" });
+        let names: Vec<String> = get_bins(Some(&object), "typescript").into_iter().map(|(name, _)| name).collect();
+        assert_eq!(names, vec!["tsc", "tsserver"]);
+        assert!(get_bins(None, "a").is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn checks_bin_links() {
+        use std::os::unix::fs::symlink;
+
+        let root = TestDir::new("global-bin");
+        let package = root.join("lib/node_modules/a");
+        let other = root.join("lib/node_modules/b");
+        let bin = root.join("bin");
+        for folder in [&package, &other, &bin].iter() {
+            fs::create_dir_all(folder).unwrap();
+        }
+        fs::write(package.join("cli.js"), "").unwrap();
+        fs::write(other.join("cli.js"), "").unwrap();
+
+        symlink(package.join("cli.js"), bin.join("linked")).unwrap();
+        symlink(other.join("cli.js"), bin.join("elsewhere")).unwrap();
+        symlink(root.join("gone"), bin.join("dangling")).unwrap();
+        fs::write(bin.join("shim"), "#!/bin/sh\nexec node \"$basedir/../lib/node_modules/a/cli.js\"\n").unwrap();
+
+        let link = |name: &str| get_bin_link(&bin.join(name), &package.join("cli.js"), &package);
+        assert_eq!(link("linked"), BinLink::Linked);
+        assert_eq!(
+            link("elsewhere"),
+            BinLink::Elsewhere(fs::canonicalize(other.join("cli.js")).unwrap())
+        );
+        assert_eq!(link("dangling"), BinLink::Dangling);
+        assert_eq!(link("shim"), BinLink::Shim);
+        assert_eq!(link("missing"), BinLink::Missing);
+    }
+}
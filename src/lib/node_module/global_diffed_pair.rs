@@ -0,0 +1,74 @@
+use crate::node_module::*;
+use crate::node_module::diffed_pair::diffed_cells;
+use crate::node_module::global_module::GlobalModule;
+use crate::semver::Semver;
+
+/// The versions of a global package in two global folders.
+pub struct GlobalDiffedPair<'a> {
+    pub name: &'a str,
+    pub version: (Option<Semver>, Option<Semver>),
+}
+
+impl<'a> GlobalDiffedPair<'a> {
+    /// Returns the GlobalDiffedPair type for each package in either folder, sorted by name.
+    pub fn get_pairs(globals: &'a [GlobalModule], diff_globals: &'a [GlobalModule]) -> Vec<Self> {
+        let version = |modules: &[GlobalModule], name: &str| {
+            modules
+                .iter()
+                .find(|module| module.name == name)
+                .and_then(|module| Semver::from(module.version.clone()))
+        };
+
+        let mut names: Vec<&str> = globals
+            .iter()
+            .chain(diff_globals.iter())
+            .map(|module| module.name.as_str())
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+
+        names
+            .into_iter()
+            .map(|name| GlobalDiffedPair {
+                name,
+                version: (version(globals, name), version(diff_globals, name)),
+            })
+            .collect()
+    }
+
+    pub fn is_different(&self) -> bool {
+        self.version.0 != self.version.1
+    }
+}
+
+impl<'a> PrintTable for GlobalDiffedPair<'a> {
+    fn table_row(&self) -> Row {
+        let (version_one, version_two) = diffed_cells(&self.version.0, &self.version.1);
+        Row::new(vec![new_cell(self.name), version_one, version_two])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn module(name: &str, version: &str) -> GlobalModule {
+        GlobalModule {
+            name: name.to_string(),
+            version: version.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn pairs_global_packages() {
+        let globals = vec![module("npm", "10.2.4"), module("typescript", "5.4.2")];
+        let diff_globals = vec![module("eslint", "8.57.0"), module("npm", "10.2.4")];
+
+        let pairs = GlobalDiffedPair::get_pairs(&globals, &diff_globals);
+        let names: Vec<&str> = pairs.iter().map(|pair| pair.name).collect();
+        assert_eq!(names, vec!["eslint", "npm", "typescript"]);
+        let different: Vec<bool> = pairs.iter().map(|pair| pair.is_different()).collect();
+        assert_eq!(different, vec![true, false, true]);
+    }
+}
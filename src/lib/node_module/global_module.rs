@@ -1,14 +1,24 @@
 use std::io::Error;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use regex::Regex;
 
+use crate::link::Link;
 use crate::pjson_detail::PjsonDetail;
 use crate::node_module::*;
+use crate::node_module::global_bin::get_bins;
 
+#[derive(Default)]
 pub struct GlobalModule {
     pub name: String,
     pub version: String,
+    pub path: PathBuf,
+    /// The names and paths of the package's executables.
+    pub bins: Vec<(String, String)>,
+    /// Where the package was fetched from, for packages installed by older versions of npm.
+    pub resolved: Option<String>,
+    /// Set when the package is symlinked into the global folder, e.g. by `npm link`.
+    pub link: Option<Link>,
 }
 
 impl NodeModule for GlobalModule {
@@ -16,9 +26,9 @@ impl NodeModule for GlobalModule {
         re.is_match(&self.name)
     }
 
-    fn populate(&mut self, path: &PathBuf, _app_pjson: Option<&PjsonDetail>) -> Result<(), Error> {
+    fn populate(&mut self, path: &Path, _app_pjson: Option<&PjsonDetail>) -> Result<(), Error> {
 
-        let PjsonDetail { name, version, .. } = match PjsonDetail::from(path) {
+        let PjsonDetail { name, version, bin, resolved, .. } = match PjsonDetail::from(path) {
             Ok(pjson_details) => pjson_details,
             Err(err) => {
                 println!("Failed to find a package.json in {:?}", path);
@@ -26,6 +36,10 @@ impl NodeModule for GlobalModule {
             },
         };
 
+        self.bins = get_bins(bin.as_ref(), &name);
+        self.resolved = resolved;
+        self.link = Link::from(path);
+        self.path = path.to_path_buf();
         self.name = name;
         self.version = version;
 
@@ -42,12 +56,3 @@ impl PrintTable for GlobalModule {
         row![c => self.name, self.version]
     }
 }
-
-impl Default for GlobalModule {
-    fn default() -> Self {
-        GlobalModule {
-            name: String::new(),
-            version: String::new(),
-        }
-    }
-}
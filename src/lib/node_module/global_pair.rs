@@ -0,0 +1,106 @@
+use prettytable::{color, Attr};
+
+use crate::node_module::*;
+use crate::node_module::diffed_pair::diffed_cells;
+use crate::node_module::global_module::GlobalModule;
+use crate::node_module::standard_module::StandardModule;
+use crate::semver::Semver;
+
+/// A global package and the project's own copy of it.
+pub struct GlobalPair<'a> {
+    pub local: &'a StandardModule,
+    pub global: &'a GlobalModule,
+    pub global_version: Option<Semver>,
+    /// Whether each version is in the range the project declares, if it declares one.
+    pub local_in_range: Option<bool>,
+    pub global_in_range: Option<bool>,
+}
+
+impl<'a> GlobalPair<'a> {
+    /// Returns the GlobalPair type for each global package that is also installed locally.
+    pub fn get_pairs(locals: &'a [StandardModule], globals: &'a [GlobalModule]) -> Vec<Self> {
+        globals
+            .iter()
+            .filter_map(|global| {
+                let local = locals.iter().find(|local| local.name == global.name)?;
+                let global_version = Semver::from(global.version.clone());
+                let range = match &local.dep_type {
+                    DepType::Dependency(spec) | DepType::DevDependency(spec) => spec.range(),
+                    DepType::ChildDependency => None,
+                };
+                let in_range = |version: &Option<Semver>| match (&range, version) {
+                    (Some(range), Some(version)) => Some(range.satisfies(version)),
+                    _ => None,
+                };
+                Some(GlobalPair {
+                    local,
+                    global,
+                    local_in_range: in_range(&local.version),
+                    global_in_range: in_range(&global_version),
+                    global_version,
+                })
+            })
+            .collect()
+    }
+
+    pub fn is_different(&self) -> bool {
+        self.local.version != self.global_version
+    }
+}
+
+fn range_cell(side: &str, in_range: Option<bool>) -> Cell {
+    match in_range {
+        Some(true) => new_cell(&format!("{} in range", side)).with_style(Attr::ForegroundColor(color::GREEN)),
+        Some(false) => new_cell(&format!("{} out of range", side)).with_style(Attr::ForegroundColor(color::RED)),
+        None => new_cell(""),
+    }
+}
+
+impl<'a> PrintTable for GlobalPair<'a> {
+    fn table_row(&self) -> Row {
+        let (local, global) = diffed_cells(&self.local.version, &self.global_version);
+        Row::new(vec![
+            new_cell(&self.local.name),
+            get_pjson_version_cell(&self.local.dep_type),
+            local,
+            global,
+            range_cell("local", self.local_in_range),
+            range_cell("global", self.global_in_range),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dependency_spec::DependencySpec;
+
+    #[test]
+    fn pairs_global_and_local_packages() {
+        let local = StandardModule {
+            name: String::from("typescript"),
+            version: Semver::from(String::from("5.3.3")),
+            dep_type: DepType::DevDependency(DependencySpec::from("~5.3.0")),
+            ..Default::default()
+        };
+        let locals = vec![local];
+        let globals = vec![
+            GlobalModule {
+                name: String::from("typescript"),
+                version: String::from("5.4.2"),
+                ..Default::default()
+            },
+            GlobalModule {
+                name: String::from("npm"),
+                version: String::from("10.2.4"),
+                ..Default::default()
+            },
+        ];
+
+        let pairs = GlobalPair::get_pairs(&locals, &globals);
+        assert_eq!(pairs.len(), 1);
+        assert!(pairs[0].is_different());
+        assert_eq!(pairs[0].local_in_range, Some(true));
+        assert_eq!(pairs[0].global_in_range, Some(false));
+    }
+}
@@ -1,29 +1,59 @@
+pub mod diffed_matrix;
 pub mod diffed_pair;
+pub mod freshness_module;
+pub mod global_bin;
+pub mod global_diffed_pair;
 pub mod global_module;
+pub mod global_pair;
+pub mod outdated_module;
 pub mod standard_module;
 
 use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::io::Error;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use prettytable::{color, row, Attr, Cell, Row};
 use regex::Regex;
 
 use crate::cli::Cli;
+use crate::dependency_spec::DependencySpec;
+use crate::link::Link;
+use crate::lockfile::Lockfile;
+use crate::node_module::standard_module::StandardModule;
 use crate::pjson_detail::PjsonDetail;
 use crate::semver::Semver;
+use crate::source::Source;
 
 pub enum DepType {
-    Dependency(Option<Semver>),
-    DevDependency(Option<Semver>),
+    Dependency(DependencySpec),
+    DevDependency(DependencySpec),
     ChildDependency,
 }
 
+impl DepType {
+    /// Returns the DepType from a name returned by `name` and the declared spec.
+    pub fn from(name: &str, spec: Option<&str>) -> Self {
+        match (name, spec) {
+            ("dependency", Some(spec)) => DepType::Dependency(DependencySpec::from(spec)),
+            ("devDependency", Some(spec)) => DepType::DevDependency(DependencySpec::from(spec)),
+            _ => DepType::ChildDependency,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DepType::Dependency(_) => "dependency",
+            DepType::DevDependency(_) => "devDependency",
+            DepType::ChildDependency => "child",
+        }
+    }
+}
+
 pub trait NodeModule {
     fn populate(
         &mut self,
-        base_path: &PathBuf,
+        base_path: &Path,
         app_pjson: Option<&PjsonDetail>,
     ) -> Result<(), Error>;
 
@@ -48,9 +78,9 @@ pub trait PrintTable {
 
 pub fn get_dep_type(name: &str, app_pjson: &PjsonDetail) -> DepType {
     match get_pjson_details(name, &app_pjson.dependencies) {
-        Some(required_version) => return DepType::Dependency(required_version),
+        Some(required_version) => DepType::Dependency(required_version),
         None => match get_pjson_details(name, &app_pjson.dev_dependencies) {
-            Some(required_version) => return DepType::DevDependency(required_version),
+            Some(required_version) => DepType::DevDependency(required_version),
             None => DepType::ChildDependency,
         },
     }
@@ -59,16 +89,143 @@ pub fn get_dep_type(name: &str, app_pjson: &PjsonDetail) -> DepType {
 pub fn get_pjson_details(
     dep_name: &str,
     required_dependencies: &Option<HashMap<String, String>>,
-) -> Option<Option<Semver>> {
+) -> Option<DependencySpec> {
     match required_dependencies {
-        Some(deps) => match deps.get(dep_name) {
-            Some(required_version) => Some(Semver::from(required_version.to_string())),
-            None => None,
-        },
+        Some(deps) => deps
+            .get(dep_name)
+            .map(|required_version| DependencySpec::from(required_version)),
         None => None,
     }
 }
 
+/// Returns the name a package was installed under, which is its folder name (including any
+/// scope folder) and can differ from the package.json name when it was installed via an alias.
+pub fn get_install_name(path: &Path) -> Option<String> {
+    let folder_name = path.file_name()?.to_str()?;
+    let scope = path
+        .parent()
+        .and_then(|parent| parent.file_name())
+        .and_then(|parent| parent.to_str())
+        .filter(|parent| parent.starts_with('@'));
+    match scope {
+        Some(scope) => Some(format!("{}/{}", scope, folder_name)),
+        None => Some(folder_name.to_string()),
+    }
+}
+
+/// Returns the packages at the top level of node_modules as the lockfile records them, for
+/// projects that are not installed. Without a package.json, every package is a child dependency.
+pub fn get_locked_deps(app_pjson: Option<&PjsonDetail>, lockfile: &Lockfile) -> Vec<StandardModule> {
+    lockfile
+        .top_level()
+        .into_iter()
+        .map(|(name, package)| StandardModule {
+            name: name.to_string(),
+            path: PathBuf::from("node_modules").join(name),
+            version: package.version.clone().and_then(Semver::from),
+            dep_type: match app_pjson {
+                Some(app_pjson) => get_dep_type(name, app_pjson),
+                None => DepType::ChildDependency,
+            },
+            resolved: package.resolved.clone(),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Returns the packages nested in the node_modules folders of the packages in `node_modules`,
+/// at any depth, as child dependencies. Links are not followed, as the packages under them
+/// belong to the project they link to.
+pub fn get_nested_deps(node_modules: &Path) -> Result<Vec<StandardModule>, Error> {
+    let mut nested = Vec::new();
+    let mut folders: Vec<PathBuf> = get_package_folders(node_modules)?
+        .into_iter()
+        .filter(|path| Link::from(path).is_none())
+        .map(|path| path.join("node_modules"))
+        .collect();
+    while let Some(folder) = folders.pop() {
+        for path in get_package_folders(&folder)? {
+            let mut dependency = StandardModule::default();
+            dependency.populate(&path, None)?;
+            if dependency.link.is_none() {
+                folders.push(path.join("node_modules"));
+            }
+            nested.push(dependency);
+        }
+    }
+    nested.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(nested)
+}
+
+/// Returns the package folders in a node_modules folder, including those under scope folders.
+fn get_package_folders(node_modules: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut folders = Vec::new();
+    if !node_modules.is_dir() {
+        return Ok(folders);
+    }
+    for entry in node_modules.read_dir()?.flatten() {
+        let folder_name = entry.file_name().to_string_lossy().to_string();
+        if folder_name.starts_with('.') {
+            continue;
+        }
+        match folder_name.starts_with('@') {
+            true => folders.extend(get_package_folders(&entry.path())?),
+            false => folders.push(entry.path()),
+        }
+    }
+    Ok(folders)
+}
+
+/// Returns the names to look up in a registry for the dependencies that were installed from one.
+pub fn get_registry_names(dependencies: &[StandardModule]) -> Vec<String> {
+    let mut names: Vec<String> = dependencies
+        .iter()
+        .filter(|dependency| is_from_registry(dependency))
+        .map(|dependency| get_package_name(dependency).to_string())
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Returns the name the package is published under, which differs from its folder name when
+/// it was installed with an alias.
+pub fn get_package_name(module: &StandardModule) -> &str {
+    match &module.dep_type {
+        DepType::Dependency(spec) | DepType::DevDependency(spec) => {
+            spec.aliased_name().unwrap_or(&module.name)
+        }
+        DepType::ChildDependency => &module.name,
+    }
+}
+
+pub fn is_from_registry(module: &StandardModule) -> bool {
+    if let DepType::Dependency(spec) | DepType::DevDependency(spec) = &module.dep_type {
+        if !spec.is_registry() {
+            return false;
+        }
+    }
+
+    match module.source() {
+        Source::ApprovedRegistry(_) | Source::PrivateRegistry(_) | Source::Unknown => {
+            module.link.is_none()
+        }
+        _ => false,
+    }
+}
+
+/// Returns true if the package is needed in production, using the lockfile's `dev` flags for
+/// packages that are not declared in the package.json.
+pub fn is_production(module: &StandardModule, lockfile: Option<&Lockfile>) -> bool {
+    match module.dep_type {
+        DepType::Dependency(_) => true,
+        DepType::DevDependency(_) => false,
+        DepType::ChildDependency => lockfile
+            .and_then(|lockfile| lockfile.get_installed(&module.path).or_else(|| lockfile.get(&module.name)))
+            .is_none_or(|locked| !locked.dev),
+    }
+}
+
 pub fn new_cell(value: &str) -> Cell {
     let mut cell = Cell::new(value);
     cell.align(prettytable::format::Alignment::CENTER);
@@ -79,26 +236,42 @@ pub fn get_pjson_version_cell(dep_type: &DepType) -> Cell {
     match dep_type {
         DepType::ChildDependency => new_cell(""),
         DepType::Dependency(pjson_version) => {
-            let cell_contents = match &pjson_version {
-                Some(version) => version.to_string(),
-                None => String::from("???"),
-            };
-            new_cell(&cell_contents)
+            new_cell(&pjson_version.to_string())
                 .with_style(Attr::BackgroundColor(color::BLUE))
                 .with_style(Attr::ForegroundColor(color::BLACK))
         }
         DepType::DevDependency(pjson_version) => {
-            let cell_contents = match &pjson_version {
-                Some(version) => version.to_string(),
-                None => String::from("???"),
-            };
-            new_cell(&cell_contents)
+            new_cell(&pjson_version.to_string())
                 .with_style(Attr::BackgroundColor(color::MAGENTA))
                 .with_style(Attr::ForegroundColor(color::BLACK))
         }
     }
 }
 
+pub fn get_link_cell(link: &Option<Link>) -> Cell {
+    match link {
+        None => new_cell(""),
+        Some(link) => {
+            let colour = match (link.dangling, link.in_repo) {
+                (true, _) => color::RED,
+                (false, false) => color::YELLOW,
+                (false, true) => color::CYAN,
+            };
+            new_cell(&link.to_string()).with_style(Attr::ForegroundColor(colour))
+        }
+    }
+}
+
+/// Returns false for packages that are not symlinks when only linked packages are shown.
+pub fn link_filter(link: &Option<Link>, linked_only: bool) -> bool {
+    !linked_only || link.is_some()
+}
+
+pub fn show_provenance() -> bool {
+    let cli = Cli::get();
+    cli.provenance || cli.source.is_some()
+}
+
 pub fn standard_filter(dep_type: &DepType) -> bool {
     let cli = Cli::get();
     match dep_type {
@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+use std::io::Error;
+
+use prettytable::{color, Attr};
+
+use crate::node_module::*;
+use crate::node_module::diffed_pair::diffed_cells;
+use crate::node_module::standard_module::StandardModule;
+use crate::registry::Packument;
+use crate::semver::Semver;
+
+pub struct OutdatedModule<'a> {
+    pub module: &'a StandardModule,
+    pub wanted: Option<Semver>,
+    pub latest: Option<Semver>,
+    pub error: Option<String>,
+}
+
+impl<'a> OutdatedModule<'a> {
+    pub fn from(
+        module: &'a StandardModule,
+        packuments: &HashMap<String, Result<Packument, Error>>,
+    ) -> Self {
+        let mut outdated = OutdatedModule {
+            module,
+            wanted: None,
+            latest: None,
+            error: None,
+        };
+
+        match packuments.get(get_package_name(module)) {
+            Some(Ok(packument)) => {
+                outdated.latest = packument.latest();
+                outdated.wanted = match &module.dep_type {
+                    DepType::Dependency(spec) | DepType::DevDependency(spec) => {
+                        packument.wanted(spec)
+                    }
+                    DepType::ChildDependency => None,
+                };
+            }
+            Some(Err(err)) => outdated.error = Some(err.to_string()),
+            None => {}
+        }
+
+        outdated
+    }
+}
+
+impl<'a> PrintTable for OutdatedModule<'a> {
+    fn table_row(&self) -> Row {
+        let (current, latest) = diffed_cells(&self.module.version, &self.latest);
+
+        let wanted_contents = match &self.wanted {
+            Some(wanted) => wanted.to_string(),
+            None => String::new(),
+        };
+        let mut wanted = new_cell(&wanted_contents);
+        if let (Some(wanted_version), Some(current_version)) = (&self.wanted, &self.module.version) {
+            if wanted_version > current_version {
+                wanted = wanted.with_style(Attr::ForegroundColor(color::YELLOW));
+            }
+        }
+
+        let latest = match &self.error {
+            Some(_) => new_cell("???").with_style(Attr::ForegroundColor(color::RED)),
+            None => latest,
+        };
+
+        Row::new(vec![
+            new_cell(&self.module.name),
+            get_pjson_version_cell(&self.module.dep_type),
+            current,
+            wanted,
+            latest,
+        ])
+    }
+}
@@ -1,26 +1,75 @@
+use std::collections::HashMap;
 use std::io::Error;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::cmp::Ordering;
 
 use regex::Regex;
 
+use crate::engines::get_engines;
+use crate::licenses::get_license_expression;
+use crate::link::Link;
 use crate::pjson_detail::PjsonDetail;
 use crate::node_module::*;
 use crate::semver::Semver;
+use crate::source::Source;
 
 pub struct StandardModule {
     pub name: String,
+    pub path: PathBuf,
     pub version: Option<Semver>,
     pub dep_type: DepType,
+    pub link: Option<Link>,
+    pub resolved: Option<String>,
+    pub license: Option<String>,
+    pub engines: HashMap<String, String>,
+    /// The newest version in the npm cache, filled in for --cached.
+    pub cached: Option<Semver>,
+    /// Whether the table this is printed in has a link column.
+    pub show_link: bool,
+}
+
+impl StandardModule {
+    /// Returns where the package was installed from.
+    pub fn source(&self) -> Source {
+        Source::from(self.resolved.as_deref(), self.link.as_ref())
+    }
 }
 
 impl NodeModule for StandardModule {
-    fn populate(&mut self, path: &PathBuf, app_pjson: Option<&PjsonDetail>) -> Result<(), Error> {
-        let PjsonDetail { name, version, .. } = PjsonDetail::from(path)?;
-        self.dep_type = get_dep_type(&name, app_pjson.unwrap());
+    fn populate(&mut self, path: &Path, app_pjson: Option<&PjsonDetail>) -> Result<(), Error> {
+        self.path = path.to_path_buf();
+        self.link = Link::from(path);
+
+        // A dangling link has no package.json to read, but is still worth reporting.
+        let (name, version, resolved, license, engines) = match &self.link {
+            Some(Link { dangling: true, .. }) => (String::new(), None, None, None, HashMap::new()),
+            _ => {
+                let PjsonDetail {
+                    name,
+                    version,
+                    resolved,
+                    license,
+                    licenses,
+                    engines,
+                    ..
+                } = PjsonDetail::from(path)?;
+                let license = get_license_expression(license.as_ref(), licenses.as_ref());
+                (name, Semver::from(version), resolved, license, get_engines(engines.as_ref()))
+            }
+        };
+
+        // Aliased packages are declared under their folder name rather than their own name.
+        let name = get_install_name(path).unwrap_or(name);
+        self.dep_type = match app_pjson {
+            Some(app_pjson) => get_dep_type(&name, app_pjson),
+            None => DepType::ChildDependency,
+        };
 
         self.name = name;
-        self.version = Semver::from(version);
+        self.version = version;
+        self.resolved = resolved;
+        self.license = license;
+        self.engines = engines;
 
         Ok(())
     }
@@ -30,7 +79,7 @@ impl NodeModule for StandardModule {
     }
 
     fn filter_by_args(&self) -> bool {
-        standard_filter(&self.dep_type)
+        standard_filter(&self.dep_type) && link_filter(&self.link, Cli::get().linked)
     }
 
     fn order(&self, to_compare: &StandardModule) -> Ordering {
@@ -44,11 +93,21 @@ impl PrintTable for StandardModule {
             Some(version) => version.to_string(),
             None => String::new(),
         };
-        Row::new(vec![
+        let mut cells = vec![
             new_cell(&self.name),
             get_pjson_version_cell(&self.dep_type),
             new_cell(&version),
-        ])
+        ];
+        if self.show_link {
+            cells.push(get_link_cell(&self.link));
+        }
+        if Cli::get().cached {
+            cells.push(get_cached_cell(&self.version, &self.cached));
+        }
+        if show_provenance() {
+            cells.push(new_cell(&self.source().to_string()));
+        }
+        Row::new(cells)
    }
 
 }
@@ -57,8 +116,26 @@ impl Default for StandardModule {
     fn default() -> Self {
         StandardModule {
             name: String::new(),
+            path: PathBuf::new(),
             version: None,
-            dep_type: DepType::ChildDependency, 
+            dep_type: DepType::ChildDependency,
+            link: None,
+            resolved: None,
+            license: None,
+            engines: HashMap::new(),
+            cached: None,
+            show_link: false,
+        }
+    }
+}
+
+/// Highlights cached versions that are newer than the installed one.
+fn get_cached_cell(version: &Option<Semver>, cached: &Option<Semver>) -> Cell {
+    match (version, cached) {
+        (Some(version), Some(cached)) if cached > version => {
+            new_cell(&cached.to_string()).with_style(Attr::ForegroundColor(color::YELLOW))
         }
+        (_, Some(cached)) => new_cell(&cached.to_string()),
+        (_, None) => new_cell(""),
     }
 }
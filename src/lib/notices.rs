@@ -0,0 +1,257 @@
+use std::fs;
+use std::io::Error;
+use std::path::Path;
+
+use crate::node_module::standard_module::StandardModule;
+
+const NOTICE_FILE_PREFIXES: [&str; 4] = ["license", "licence", "copying", "notice"];
+const NOTICE_FILE_EXTENSIONS: [&str; 4] = ["md", "markdown", "txt", "rst"];
+
+/// A package listed in the attributions document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoticePackage {
+    pub name: String,
+    pub version: String,
+    pub license: String,
+}
+
+/// A license or notice text, shared by every package that ships an identical copy.
+#[derive(Debug, PartialEq)]
+pub struct NoticeText {
+    pub text: String,
+    pub packages: Vec<NoticePackage>,
+}
+
+/// The attributions for a set of packages.
+#[derive(Debug, Default)]
+pub struct Notices {
+    pub texts: Vec<NoticeText>,
+    /// Packages that do not ship any license or notice files.
+    pub without_texts: Vec<NoticePackage>,
+}
+
+impl Notices {
+    /// Returns the Notices type for the packages, reading the license files from their folders.
+    pub fn from(dependencies: &[&StandardModule]) -> Result<Self, Error> {
+        let mut notices = Notices::default();
+        for dependency in dependencies {
+            let package = NoticePackage {
+                name: dependency.name.clone(),
+                version: dependency
+                    .version
+                    .as_ref()
+                    .map(|version| version.to_string())
+                    .unwrap_or_default(),
+                license: dependency.license.clone().unwrap_or_default(),
+            };
+            let texts = read_notice_files(&dependency.path)?;
+            notices.add(package, texts);
+        }
+        Ok(notices)
+    }
+
+    /// Adds a package and its texts, grouping it with any package that has an identical text.
+    pub fn add(&mut self, package: NoticePackage, texts: Vec<String>) {
+        if texts.is_empty() {
+            self.without_texts.push(package);
+            return;
+        }
+        let mut unique: Vec<String> = Vec::new();
+        for text in texts.iter().map(|text| normalise(text)) {
+            if !unique.contains(&text) {
+                unique.push(text);
+            }
+        }
+        for text in unique {
+            match self.texts.iter_mut().find(|notice| notice.text == text) {
+                Some(notice) => notice.packages.push(package.clone()),
+                None => self.texts.push(NoticeText {
+                    text,
+                    packages: vec![package.clone()],
+                }),
+            }
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut document = String::from("THIRD-PARTY SOFTWARE NOTICES\n");
+        for notice in &self.texts {
+            document.push_str(&format!("\n{}\n\n", "=".repeat(80)));
+            for package in &notice.packages {
+                document.push_str(&format!("{}\n", describe(package)));
+            }
+            document.push_str(&format!("\n{}\n", notice.text));
+        }
+        if !self.without_texts.is_empty() {
+            document.push_str(&format!("\n{}\n\nPackages without license files:\n\n", "=".repeat(80)));
+            for package in &self.without_texts {
+                document.push_str(&format!("{}\n", describe(package)));
+            }
+        }
+        document
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut document = String::from("# Third-party software notices\n");
+        for notice in &self.texts {
+            let names: Vec<&str> = notice.packages.iter().map(|package| package.name.as_str()).collect();
+            document.push_str(&format!("\n## {}\n\n", names.join(", ")));
+            for package in &notice.packages {
+                document.push_str(&format!("- {}\n", describe(package)));
+            }
+            let fence = get_fence(&notice.text);
+            document.push_str(&format!("\n{}\n{}\n{}\n", fence, notice.text, fence));
+        }
+        if !self.without_texts.is_empty() {
+            document.push_str("\n## Packages without license files\n\n");
+            for package in &self.without_texts {
+                document.push_str(&format!("- {}\n", describe(package)));
+            }
+        }
+        document
+    }
+}
+
+/// Returns a code fence longer than any run of backticks in the text, so the text can't end it.
+fn get_fence(text: &str) -> String {
+    let longest = text
+        .split(|c| c != '`')
+        .map(|run| run.len())
+        .max()
+        .unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+fn describe(package: &NoticePackage) -> String {
+    let license = match package.license.is_empty() {
+        true => "no license declared",
+        false => &package.license,
+    };
+    format!("{} {} - {}", package.name, package.version, license)
+}
+
+/// Returns the contents of the LICENSE, LICENCE, COPYING and NOTICE files in a package folder.
+fn read_notice_files(path: &Path) -> Result<Vec<String>, Error> {
+    if !path.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut files: Vec<_> = fs::read_dir(path)?
+        .flatten()
+        .filter(|entry| entry.path().is_file())
+        .filter(|entry| is_notice_file(&entry.file_name().to_string_lossy()))
+        .map(|entry| entry.path())
+        .collect();
+    files.sort();
+
+    let mut texts = Vec::new();
+    for file in files {
+        let bytes = fs::read(file)?;
+        texts.push(String::from_utf8_lossy(&bytes).to_string());
+    }
+    Ok(texts)
+}
+
+/// Returns true for names such as `LICENSE`, `LICENSE-MIT` or `NOTICE.md`, but not source files
+/// such as `license.js`.
+fn is_notice_file(name: &str) -> bool {
+    let name = name.to_lowercase();
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension)) => (stem, Some(extension)),
+        None => (name.as_str(), None),
+    };
+    if extension.is_some_and(|extension| !NOTICE_FILE_EXTENSIONS.contains(&extension)) {
+        return false;
+    }
+    NOTICE_FILE_PREFIXES.iter().any(|prefix| match stem.strip_prefix(prefix) {
+        Some(rest) => rest.is_empty() || rest.starts_with('-') || rest.starts_with('_'),
+        None => false,
+    })
+}
+
+/// Normalises line endings and surrounding whitespace so copies that differ only by those are
+/// treated as identical.
+fn normalise(text: &str) -> String {
+    text.replace("\r\n", "\n")
+        .lines()
+        .map(|line| line.trim_end())
+        .collect::<Vec<&str>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_module::get_nested_deps;
+    use crate::test_dir::{write, TestDir};
+
+    fn package(name: &str, license: &str) -> NoticePackage {
+        NoticePackage {
+            name: name.to_string(),
+            version: String::from("1.0.0"),
+            license: license.to_string(),
+        }
+    }
+
+    #[test]
+    fn deduplicates_identical_texts() {
+        let mut notices = Notices::default();
+        notices.add(package("a", "MIT"), vec![String::from("MIT License\r\n\r\nCopyright  \r\n")]);
+        notices.add(package("b", "MIT"), vec![String::from("MIT License\n\nCopyright\n")]);
+        notices.add(package("c", "ISC"), vec![String::from("ISC License"), String::from("ISC License\n")]);
+        notices.add(package("d", ""), vec![]);
+
+        assert_eq!(notices.texts.len(), 2);
+        assert_eq!(notices.texts[0].packages, vec![package("a", "MIT"), package("b", "MIT")]);
+        assert_eq!(notices.texts[1].packages, vec![package("c", "ISC")]);
+        assert_eq!(notices.without_texts, vec![package("d", "")]);
+    }
+
+    #[test]
+    fn renders_markdown() {
+        let mut notices = Notices::default();
+        notices.add(package("a", "MIT"), vec![String::from("MIT License")]);
+        notices.add(package("d", ""), vec![]);
+
+        assert_eq!(
+            notices.to_markdown(),
+            "# Third-party software notices\n\
+             \n## a\n\n- a 1.0.0 - MIT\n\n```\nMIT License\n```\n\
+             \n## Packages without license files\n\n- d 1.0.0 - no license declared\n"
+        );
+
+        let mut fenced = Notices::default();
+        fenced.add(package("e", "MIT"), vec![String::from("Use ```code``` or ````more````.")]);
+        assert!(fenced.to_markdown().contains("\n`````\nUse ```code``` or ````more````.\n`````\n"));
+    }
+
+    #[test]
+    fn finds_notice_files() {
+        for name in ["LICENSE", "licence.md", "LICENSE-MIT", "NOTICE.txt", "COPYING", "LICENSE_APACHE.txt"].iter() {
+            assert!(is_notice_file(name), "{}", name);
+        }
+        for name in ["license.js", "notice.json", "licenses", "README.md", "LICENSE-MIT.js"].iter() {
+            assert!(!is_notice_file(name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn includes_nested_packages() {
+        let root = TestDir::new("nested-notices");
+        write(
+            root.join("node_modules/a/package.json"),
+            r#"{ "name": "a", "version": "1.0.0", "license": "MIT" }"#,
+        );
+        write(
+            root.join("node_modules/a/node_modules/b/package.json"),
+            r#"{ "name": "b", "version": "1.0.0", "license": "ISC" }"#,
+        );
+        write(root.join("node_modules/a/node_modules/b/LICENSE"), "ISC License\n");
+
+        let nested = get_nested_deps(&root.join("node_modules")).unwrap();
+        let notices = Notices::from(&nested.iter().collect::<Vec<_>>()).unwrap();
+        assert_eq!(notices.texts.len(), 1);
+        assert_eq!(notices.texts[0].packages, vec![package("b", "ISC")]);
+    }
+}
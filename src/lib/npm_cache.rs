@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::npmrc::Npmrc;
+use crate::registry::Packument;
+use crate::semver::Semver;
+
+const REQUEST_CACHE_PREFIX: &str = "make-fetch-happen:request-cache:";
+
+/// The content-addressable cache npm keeps in `~/.npm/_cacache`.
+pub struct NpmCache {
+    pub path: PathBuf,
+}
+
+/// A line in an `index-v5` bucket file.
+#[derive(Deserialize)]
+struct IndexEntry {
+    key: String,
+    integrity: Option<String>,
+
+    #[serde(default)]
+    time: u64,
+}
+
+/// What the index records for each package name.
+#[derive(Default)]
+struct CacheIndex {
+    /// The integrity and time of the newest packument entry.
+    packuments: HashMap<String, (String, u64)>,
+    /// The newest version with a cached tarball.
+    tarballs: HashMap<String, Semver>,
+}
+
+impl NpmCache {
+    /// Returns the NpmCache type for `cache`, or else the `cache` setting in .npmrc, or else
+    /// `~/.npm`.
+    pub fn from(npmrc: &Npmrc, cache: Option<&Path>) -> Self {
+        let cache = match cache {
+            Some(cache) => cache.to_path_buf(),
+            None => match npmrc.get("cache") {
+                Some(cache) => PathBuf::from(cache),
+                None => PathBuf::from(env::var("HOME").unwrap_or_default()).join(".npm"),
+            },
+        };
+        NpmCache {
+            path: cache.join("_cacache"),
+        }
+    }
+
+    /// Returns the most recently cached packument for each name, without any network access.
+    pub fn packuments(&self, names: Vec<String>) -> HashMap<String, Result<Packument, Error>> {
+        let index = match self.read_index() {
+            Ok(index) => index,
+            Err(err) => {
+                let message = format!("failed to read {}: {}", self.path.display(), err);
+                return names
+                    .into_iter()
+                    .map(|name| (name, Err(Error::other(message.clone()))))
+                    .collect();
+            }
+        };
+
+        names
+            .into_iter()
+            .map(|name| {
+                let packument = match index.packuments.get(&name) {
+                    Some((integrity, _)) => self.read_content(integrity),
+                    None => Err(Error::new(ErrorKind::NotFound, "not in the npm cache")),
+                };
+                (name, packument)
+            })
+            .collect()
+    }
+
+    /// Returns the newest version of each package that the cache knows of, from its latest
+    /// cached packument and its cached tarballs, leaving out packages it has neither for.
+    pub fn newest_versions(&self, names: &[String]) -> Result<HashMap<String, Semver>, Error> {
+        let index = self
+            .read_index()
+            .map_err(|err| Error::new(err.kind(), format!("failed to read {}: {}", self.path.display(), err)))?;
+        let mut versions = HashMap::new();
+        for name in names {
+            let packument = index
+                .packuments
+                .get(name)
+                .and_then(|(integrity, _)| self.read_content(integrity).ok())
+                .and_then(|packument| packument.latest());
+            let newest = packument.into_iter().chain(index.tarballs.get(name).cloned()).max();
+            if let Some(newest) = newest {
+                versions.insert(name.clone(), newest);
+            }
+        }
+        Ok(versions)
+    }
+
+    /// Returns the newest packument entry and tarball version for each package name.
+    fn read_index(&self) -> Result<CacheIndex, Error> {
+        let mut index = CacheIndex::default();
+        let mut buckets = vec![self.path.join("index-v5")];
+
+        while let Some(dir) = buckets.pop() {
+            for entry in fs::read_dir(&dir)?.flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    buckets.push(path);
+                    continue;
+                }
+
+                // Entries are appended, so later lines replace earlier ones for the same key.
+                let mut latest: HashMap<String, IndexEntry> = HashMap::new();
+                for line in fs::read_to_string(&path)?.lines() {
+                    let json = match line.find('\t') {
+                        Some(i) => &line[i + 1..],
+                        None => continue,
+                    };
+                    if let Ok(entry) = serde_json::from_str::<IndexEntry>(json) {
+                        latest.insert(entry.key.clone(), entry);
+                    }
+                }
+
+                for entry in latest.into_values() {
+                    if let Some((name, version)) = get_tarball(&entry.key) {
+                        if index.tarballs.get(&name).is_none_or(|newest| version > *newest) {
+                            index.tarballs.insert(name, version);
+                        }
+                        continue;
+                    }
+                    let name = match get_packument_name(&entry.key) {
+                        Some(name) => name,
+                        None => continue,
+                    };
+                    let integrity = match entry.integrity {
+                        Some(integrity) => integrity,
+                        None => continue,
+                    };
+                    let time = entry.time;
+                    if index.packuments.get(&name).is_none_or(|(_, newest)| time > *newest) {
+                        index.packuments.insert(name, (integrity, time));
+                    }
+                }
+            }
+        }
+
+        Ok(index)
+    }
+
+    fn read_content(&self, integrity: &str) -> Result<Packument, Error> {
+        let path = self
+            .content_path(integrity)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "invalid integrity"))?;
+        Packument::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Returns the path of the content for an integrity such as `sha512-<base64>`.
+    pub fn content_path(&self, integrity: &str) -> Option<PathBuf> {
+        let integrity = integrity.split_whitespace().next()?;
+        let (algorithm, digest) = integrity.split_at(integrity.find('-')?);
+        let hex: String = decode_base64(&digest[1..])?
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        if hex.len() < 5 {
+            return None;
+        }
+
+        Some(
+            self.path
+                .join("content-v2")
+                .join(algorithm)
+                .join(&hex[0..2])
+                .join(&hex[2..4])
+                .join(&hex[4..]),
+        )
+    }
+}
+
+/// Returns the package name for a cached packument request, ignoring tarballs and other keys.
+fn get_packument_name(key: &str) -> Option<String> {
+    let url = key.strip_prefix(REQUEST_CACHE_PREFIX)?;
+    if url.contains("/-/") {
+        return None;
+    }
+    get_name_from_url(url)
+}
+
+/// Returns the package name and version for a cached tarball request such as
+/// `<registry>/@s/a/-/a-1.0.0.tgz`.
+fn get_tarball(key: &str) -> Option<(String, Semver)> {
+    let url = key.strip_prefix(REQUEST_CACHE_PREFIX)?;
+    let (package_url, file) = url.split_once("/-/")?;
+    let name = get_name_from_url(package_url)?;
+    let unscoped = name.rsplit('/').next()?;
+    let version = file.strip_prefix(unscoped)?.strip_prefix('-')?.strip_suffix(".tgz")?;
+    Some((name, Semver::from(version.to_string())?))
+}
+
+/// Returns the package name at the end of a registry URL, where a scoped name is either two
+/// path segments or one with an encoded slash.
+fn get_name_from_url(url: &str) -> Option<String> {
+    let mut segments = url.rsplit('/');
+    let last = segments.next()?.replace("%2f", "/").replace("%2F", "/");
+    let name = match segments.next() {
+        Some(scope) if scope.starts_with('@') && !last.contains('/') => format!("{}/{}", scope, last),
+        _ => last,
+    };
+    if name.is_empty() || (name.starts_with('@') && !name.contains('/')) {
+        return None;
+    }
+    Some(name)
+}
+
+fn decode_base64(encoded: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for c in encoded.bytes().filter(|c| *c != b'=') {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::{write, TestDir};
+
+    #[test]
+    fn reads_packuments_from_the_cache() {
+        let root = TestDir::new("cacache");
+        let cache = NpmCache {
+            path: root.join("_cacache"),
+        };
+
+        let old = r#"{"name":"a","dist-tags":{"latest":"1.0.0"},"versions":{"1.0.0":{}}}"#;
+        let new = r#"{"name":"a","dist-tags":{"latest":"1.1.0"},"versions":{"1.0.0":{},"1.1.0":{}}}"#;
+        write(cache.content_path("sha512-AAEC").unwrap(), old);
+        write(cache.content_path("sha512-AAED").unwrap(), new);
+
+        let key = "make-fetch-happen:request-cache:https://registry.npmjs.org/a";
+        let tarball = "make-fetch-happen:request-cache:https://registry.npmjs.org/a/-/a-1.1.0.tgz";
+        write(
+            cache.path.join("index-v5/00/11/2233"),
+            &format!(
+                "x\t{{\"key\":\"{}\",\"integrity\":\"sha512-AAEC\",\"time\":1}}\n\
+                 x\t{{\"key\":\"{}\",\"integrity\":\"sha512-AAED\",\"time\":2}}\n\
+                 x\t{{\"key\":\"{}\",\"integrity\":\"sha512-AAEE\",\"time\":3}}\n",
+                key, key, tarball
+            ),
+        );
+        let scoped = "make-fetch-happen:request-cache:https://registry.npmjs.org/@s/c/-/c-2.0.0.tgz";
+        write(
+            cache.path.join("index-v5/44/55/6677"),
+            &format!("x\t{{\"key\":\"{}\",\"integrity\":\"sha512-AAEF\",\"time\":4}}\n", scoped),
+        );
+
+        let packuments = cache.packuments(vec![String::from("a"), String::from("b")]);
+        let a = packuments["a"].as_ref().unwrap();
+        assert_eq!(a.latest().unwrap().to_string(), "1.1.0");
+        assert_eq!(packuments["b"].as_ref().unwrap_err().kind(), ErrorKind::NotFound);
+
+        let names = [String::from("a"), String::from("b"), String::from("@s/c")];
+        let newest = cache.newest_versions(&names).unwrap();
+        assert_eq!(newest["a"].to_string(), "1.1.0");
+        assert_eq!(newest["@s/c"].to_string(), "2.0.0");
+        assert!(!newest.contains_key("b"));
+    }
+
+    #[test]
+    fn finds_content_paths() {
+        let cache = NpmCache {
+            path: PathBuf::from("/cache"),
+        };
+        assert_eq!(
+            cache.content_path("sha512-AAECAw==").unwrap(),
+            PathBuf::from("/cache/content-v2/sha512/00/01/0203")
+        );
+    }
+
+    #[test]
+    fn reads_packument_names_from_keys() {
+        let name = |key: &str| get_packument_name(&format!("{}{}", REQUEST_CACHE_PREFIX, key));
+        assert_eq!(name("https://registry.npmjs.org/lodash").as_deref(), Some("lodash"));
+        assert_eq!(name("https://registry.npmjs.org/@s%2fa").as_deref(), Some("@s/a"));
+        assert_eq!(name("https://registry.npmjs.org/a/-/a-1.0.0.tgz"), None);
+    }
+
+    #[test]
+    fn reads_tarball_versions_from_keys() {
+        let tarball = |key: &str| {
+            get_tarball(&format!("{}{}", REQUEST_CACHE_PREFIX, key)).map(|(name, version)| (name, version.to_string()))
+        };
+        assert_eq!(
+            tarball("https://registry.npmjs.org/left-pad/-/left-pad-1.3.0.tgz"),
+            Some((String::from("left-pad"), String::from("1.3.0")))
+        );
+        assert_eq!(
+            tarball("https://npm.ourco.com/@s/a/-/a-2.0.0-beta.1.tgz"),
+            Some((String::from("@s/a"), String::from("2.0.0-beta.1")))
+        );
+        assert_eq!(tarball("https://registry.npmjs.org/left-pad"), None);
+    }
+}
@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io::Error;
+use std::path::{Path, PathBuf};
+
+use regex::{Captures, Regex};
+
+pub const DEFAULT_REGISTRY: &str = "https://registry.npmjs.org/";
+
+/// The merged npm configuration for a project, from its `.npmrc`, the user's `.npmrc` and
+/// `npm_config_*` environment variables.
+#[derive(Debug, Default)]
+pub struct Npmrc {
+    pub values: HashMap<String, String>,
+}
+
+impl Npmrc {
+    /// Returns the Npmrc type for the project at `base_path`.
+    pub fn from(base_path: &Path) -> Result<Npmrc, Error> {
+        let mut npmrc = Npmrc::default();
+
+        let mut files = Vec::new();
+        if let Some(user_config) = get_user_config_path() {
+            files.push(user_config);
+        }
+        files.push(base_path.join(".npmrc"));
+
+        npmrc.merge_files(&files)?;
+        npmrc.merge_env();
+        Ok(npmrc)
+    }
+
+    /// Returns the Npmrc type npm uses for global commands, from its builtin and global `npmrc`
+    /// files under the Node prefix, the user's `.npmrc` and `npm_config_*` environment variables.
+    /// Project `.npmrc` files are not read for global commands.
+    pub fn global(node_prefix: Option<&Path>) -> Result<Npmrc, Error> {
+        let mut npmrc = Npmrc::default();
+
+        let mut files = Vec::new();
+        if let Some(node_prefix) = node_prefix {
+            files.push(node_prefix.join("lib/node_modules/npm/npmrc"));
+        }
+        match env::var_os("NPM_CONFIG_GLOBALCONFIG") {
+            Some(path) => files.push(PathBuf::from(path)),
+            None => {
+                if let Some(node_prefix) = node_prefix {
+                    files.push(node_prefix.join("etc/npmrc"));
+                }
+            }
+        }
+        if let Some(user_config) = get_user_config_path() {
+            files.push(user_config);
+        }
+
+        npmrc.merge_files(&files)?;
+        npmrc.merge_env();
+        Ok(npmrc)
+    }
+
+    fn merge_files(&mut self, files: &[PathBuf]) -> Result<(), Error> {
+        for file in files {
+            if file.is_file() {
+                self.merge(&fs::read_to_string(file)?);
+            }
+        }
+        Ok(())
+    }
+
+    fn merge_env(&mut self) {
+        for (key, value) in env::vars() {
+            if key.to_lowercase().starts_with("npm_config_") {
+                let key = key["npm_config_".len()..].to_lowercase().replace('_', "-");
+                self.values.insert(key, value);
+            }
+        }
+    }
+
+    /// Returns the Npmrc type for the contents of a single file.
+    pub fn parse(contents: &str) -> Npmrc {
+        let mut npmrc = Npmrc::default();
+        npmrc.merge(contents);
+        npmrc
+    }
+
+    /// Adds the settings in `contents`, overriding any that are already set.
+    pub fn merge(&mut self, contents: &str) {
+        let env_re = Regex::new(r#"\$\{([^}]+)\}"#).unwrap();
+
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            let (key, value) = match line.find('=') {
+                Some(i) => (line[..i].trim(), line[i + 1..].trim()),
+                None => continue,
+            };
+            let value = value.trim_matches('"').trim_matches('\'');
+            let value = env_re.replace_all(value, |captures: &Captures| {
+                env::var(&captures[1]).unwrap_or_default()
+            });
+            self.values.insert(key.to_string(), value.to_string());
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(|value| value.as_str())
+    }
+
+    /// Returns the default registry, always ending in a slash.
+    pub fn registry(&self) -> String {
+        with_trailing_slash(self.get("registry").unwrap_or(DEFAULT_REGISTRY))
+    }
+
+    /// Returns the registry configured with `@scope:registry=` for a scope, if there is one.
+    pub fn scope_registry(&self, scope: &str) -> Option<String> {
+        self.get(&format!("{}:registry", scope))
+            .map(with_trailing_slash)
+    }
+
+    /// Returns every registry configured with `@scope:registry=`, always ending in a slash.
+    pub fn scope_registries(&self) -> Vec<String> {
+        self.values
+            .iter()
+            .filter(|(key, _)| key.starts_with('@') && key.ends_with(":registry"))
+            .map(|(_, registry)| with_trailing_slash(registry))
+            .collect()
+    }
+
+    /// Returns the registry that a package should be fetched from.
+    pub fn registry_for(&self, package_name: &str) -> String {
+        get_scope(package_name)
+            .and_then(|scope| self.scope_registry(scope))
+            .unwrap_or_else(|| self.registry())
+    }
+
+    /// Returns the `_authToken` configured for a registry URL, matching the longest
+    /// `//host/path/:_authToken=` key that prefixes it.
+    pub fn auth_token(&self, registry: &str) -> Option<&str> {
+        let without_scheme = strip_scheme(registry);
+        self.values
+            .iter()
+            .filter_map(|(key, value)| {
+                let prefix = key.strip_suffix(":_authToken")?.strip_prefix("//")?;
+                match without_scheme.starts_with(prefix) {
+                    true => Some((prefix.len(), value.as_str())),
+                    false => None,
+                }
+            })
+            .max_by_key(|(length, _)| *length)
+            .map(|(_, value)| value)
+    }
+}
+
+/// Returns the `@scope` part of a scoped package name.
+pub fn get_scope(package_name: &str) -> Option<&str> {
+    match package_name.starts_with('@') {
+        true => package_name.split('/').next(),
+        false => None,
+    }
+}
+
+/// Returns the URL without its protocol, so http and https registries compare equal.
+pub fn strip_scheme(url: &str) -> &str {
+    match url.find("://") {
+        Some(i) => &url[i + 3..],
+        None => url.trim_start_matches("//"),
+    }
+}
+
+fn with_trailing_slash(url: &str) -> String {
+    match url.ends_with('/') {
+        true => url.to_string(),
+        false => format!("{}/", url),
+    }
+}
+
+fn get_user_config_path() -> Option<PathBuf> {
+    if let Ok(path) = env::var("NPM_CONFIG_USERCONFIG") {
+        return Some(PathBuf::from(path));
+    }
+    env::var("HOME")
+        .ok()
+        .map(|home| PathBuf::from(home).join(".npmrc"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_registries() {
+        let npmrc = Npmrc::parse(
+            "; comment\n\
+             registry=http://localhost:4873\n\
+             @ourco:registry = \"https://npm.ourco.com/api/\"\n",
+        );
+        assert_eq!(npmrc.registry(), "http://localhost:4873/");
+        assert_eq!(npmrc.registry_for("left-pad"), "http://localhost:4873/");
+        assert_eq!(npmrc.registry_for("@ourco/ui"), "https://npm.ourco.com/api/");
+        assert_eq!(npmrc.registry_for("@other/ui"), "http://localhost:4873/");
+    }
+
+    #[test]
+    fn defaults_to_public_registry() {
+        assert_eq!(Npmrc::parse("").registry(), DEFAULT_REGISTRY);
+    }
+
+    #[test]
+    fn finds_auth_tokens() {
+        let npmrc = Npmrc::parse(
+            "//npm.ourco.com/:_authToken=abc\n\
+             //npm.ourco.com/api/:_authToken=def\n",
+        );
+        assert_eq!(npmrc.auth_token("https://npm.ourco.com/api/"), Some("def"));
+        assert_eq!(npmrc.auth_token("https://npm.ourco.com/other/"), Some("abc"));
+        assert_eq!(npmrc.auth_token("https://registry.npmjs.org/"), None);
+    }
+}
@@ -1,8 +1,9 @@
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::{Error, Read};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -18,6 +19,28 @@ pub struct PjsonDetail {
 
     #[serde(default = "default_to_none")]
     pub peer_dependencies: Option<HashMap<String, String>>,
+
+    /// Written into installed package.json files by older versions of npm.
+    #[serde(rename = "_resolved")]
+    pub resolved: Option<String>,
+
+    /// An SPDX expression, or the legacy `{ "type": ... }` object form.
+    pub license: Option<Value>,
+
+    /// The legacy form of `license`, usually an array of strings or `{ "type": ... }` objects.
+    pub licenses: Option<Value>,
+
+    /// An object of engine ranges, or the legacy array form such as `["node >= 0.8"]`.
+    pub engines: Option<Value>,
+
+    /// A path to the package's executable, or an object of executable names and paths.
+    pub bin: Option<Value>,
+
+    /// The tool versions pinned for Volta, e.g. `{ "node": "20.11.1", "npm": "10.2.4" }`.
+    pub volta: Option<Value>,
+
+    /// The package manager pinned for Corepack, e.g. `pnpm@8.15.4`.
+    pub package_manager: Option<String>,
 }
 
 fn default_to_none() -> Option<HashMap<String, String>> {
@@ -26,18 +49,22 @@ fn default_to_none() -> Option<HashMap<String, String>> {
 
 impl PjsonDetail {
     /// Returns the PjsonDetail type.
-    pub fn from(base_path: &PathBuf) -> Result<PjsonDetail, Error> {
+    pub fn from(base_path: &Path) -> Result<PjsonDetail, Error> {
         let mut path = PathBuf::from(base_path);
         path.push("package.json");
 
         let pjson_string = Self::get_pjson(&path)?;
-        let pjson_details: PjsonDetail = serde_json::from_str(&pjson_string[..])?;
+        Self::parse(&pjson_string)
+    }
 
+    /// Returns the PjsonDetail type from the contents of a package.json.
+    pub fn parse(contents: &str) -> Result<PjsonDetail, Error> {
+        let pjson_details: PjsonDetail = serde_json::from_str(contents)?;
         Ok(pjson_details)
     }
 
     /// Returns the data from the package.json file.
-    fn get_pjson(path: &PathBuf) -> Result<String, Error> {
+    fn get_pjson(path: &Path) -> Result<String, Error> {
         let mut file = File::open(path)?;
         let mut contents = String::new();
         file.read_to_string(&mut contents)?;
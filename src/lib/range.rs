@@ -0,0 +1,387 @@
+use std::cmp::Ordering;
+
+use regex::Regex;
+
+use crate::semver::Semver;
+
+/// An npm semver range such as `^1.2.3`, `>=1.0.0 <2 || 3.x` or `1.2 - 2`.
+#[derive(Debug)]
+pub struct Range {
+    sets: Vec<Vec<Comparator>>,
+}
+
+#[derive(Debug)]
+struct Comparator {
+    operator: Ordering,
+    inclusive: bool,
+    version: Semver,
+}
+
+impl Range {
+    /// Returns the Range type, or None if the string is not a valid range.
+    pub fn parse(range: &str) -> Option<Range> {
+        let operator_re = Regex::new(r#"([<>=~^]+)\s+"#).unwrap();
+        let hyphen_re = Regex::new(r#"^(\S+)\s+-\s+(\S+)$"#).unwrap();
+
+        let mut sets = Vec::new();
+        for set in range.split("||") {
+            let set = set.trim();
+            let mut comparators = Vec::new();
+
+            if let Some(captures) = hyphen_re.captures(set) {
+                let from = Partial::parse(&captures[1])?;
+                let to = Partial::parse(&captures[2])?;
+                comparators.push(from.lower_bound());
+                comparators.extend(to.at_most());
+            } else {
+                let set = operator_re.replace_all(set, "$1");
+                for token in set.split_whitespace() {
+                    comparators.extend(parse_comparator(token)?);
+                }
+            }
+            sets.push(comparators);
+        }
+
+        Some(Range { sets })
+    }
+
+    /// Returns true if the version is within the range. Pre-releases only satisfy a range that
+    /// names a pre-release of the same major, minor and patch, as with npm.
+    pub fn satisfies(&self, version: &Semver) -> bool {
+        self.matches(version, false)
+    }
+
+    /// Returns true if the version is within the range, letting a pre-release satisfy any range
+    /// it falls in, as npm does when it checks engines.
+    pub fn satisfies_including_prerelease(&self, version: &Semver) -> bool {
+        self.matches(version, true)
+    }
+
+    fn matches(&self, version: &Semver, include_prerelease: bool) -> bool {
+        self.sets.iter().any(|set| {
+            if !set.iter().all(|comparator| comparator.matches(version)) {
+                return false;
+            }
+            if include_prerelease || version.pre_release.is_none() {
+                return true;
+            }
+            set.iter().any(|comparator| {
+                comparator.version.pre_release.is_some()
+                    && comparator.version.numbers() == version.numbers()
+            })
+        })
+    }
+
+    /// Returns true if some version satisfies both ranges.
+    pub fn intersects(&self, other: &Range) -> bool {
+        self.sets.iter().any(|set| {
+            other.sets.iter().any(|other_set| {
+                let comparators: Vec<&Comparator> = set.iter().chain(other_set.iter()).collect();
+                is_satisfiable(&comparators)
+            })
+        })
+    }
+
+    /// Returns the lowest version allowed by each part of the range that has an inclusive lower
+    /// bound, such as 1.2.3 for `>=1.2.3` or `^1.2.3`.
+    pub fn min_versions(&self) -> Vec<&Semver> {
+        self.sets
+            .iter()
+            .filter_map(|set| {
+                set.iter()
+                    .filter(|comparator| comparator.operator != Ordering::Less && comparator.inclusive)
+                    .map(|comparator| &comparator.version)
+                    .max()
+            })
+            .collect()
+    }
+
+    /// Returns the highest of the versions that satisfies the range.
+    pub fn max_satisfying<'a, I>(&self, versions: I) -> Option<&'a Semver>
+    where
+        I: IntoIterator<Item = &'a Semver>,
+    {
+        versions
+            .into_iter()
+            .filter(|version| self.satisfies(version))
+            .max()
+    }
+}
+
+/// Returns true if some version matches every comparator, ignoring pre-release restrictions.
+fn is_satisfiable(comparators: &[&Comparator]) -> bool {
+    if let Some(exact) = comparators.iter().find(|comparator| comparator.operator == Ordering::Equal) {
+        return comparators.iter().all(|comparator| comparator.matches(&exact.version));
+    }
+
+    let tighter = |a: &Comparator, b: &Comparator, ordering: Ordering| {
+        a.version.cmp(&b.version) == ordering || (a.version.same_version(&b.version) && !a.inclusive)
+    };
+    let mut lower: Option<&Comparator> = None;
+    let mut upper: Option<&Comparator> = None;
+    for comparator in comparators.iter() {
+        match comparator.operator {
+            Ordering::Greater if lower.is_none_or(|lower| tighter(comparator, lower, Ordering::Greater)) => {
+                lower = Some(comparator)
+            }
+            Ordering::Less if upper.is_none_or(|upper| tighter(comparator, upper, Ordering::Less)) => {
+                upper = Some(comparator)
+            }
+            _ => {}
+        }
+    }
+
+    match (lower, upper) {
+        (Some(lower), Some(upper)) => {
+            lower.version < upper.version
+                || (lower.version.same_version(&upper.version) && lower.inclusive && upper.inclusive)
+        }
+        _ => true,
+    }
+}
+
+impl Comparator {
+    fn new(operator: Ordering, inclusive: bool, version: Semver) -> Self {
+        Comparator {
+            operator,
+            inclusive,
+            version,
+        }
+    }
+
+    fn matches(&self, version: &Semver) -> bool {
+        let ordering = version.cmp(&self.version);
+        ordering == self.operator || (self.inclusive && ordering == Ordering::Equal)
+    }
+}
+
+/// A version where any of the parts may be missing or a wildcard, e.g. `1`, `1.2.x` or `*`.
+struct Partial {
+    major: Option<u64>,
+    minor: Option<u64>,
+    patch: Option<u64>,
+    pre_release: Option<String>,
+}
+
+impl Partial {
+    fn parse(partial: &str) -> Option<Partial> {
+        let partial_re = Regex::new(
+            r#"^v?(\*|x|X|\d+)(?:\.(\*|x|X|\d+)(?:\.(\*|x|X|\d+)(?:-([.\-0-9a-zA-Z]+))?(?:\+[.\-0-9a-zA-Z]+)?)?)?$"#,
+        )
+        .unwrap();
+        let captures = partial_re.captures(partial)?;
+        let number = |i| captures.get(i).and_then(|part: regex::Match| part.as_str().parse().ok());
+
+        Some(Partial {
+            major: number(1),
+            minor: number(2),
+            patch: number(3),
+            pre_release: captures.get(4).map(|part| part.as_str().to_string()),
+        })
+    }
+
+    fn lower_bound(&self) -> Comparator {
+        let version = make(
+            self.major.unwrap_or(0),
+            self.minor.unwrap_or(0),
+            self.patch.unwrap_or(0),
+            self.pre_release.clone(),
+        );
+        Comparator::new(Ordering::Greater, true, version)
+    }
+
+    /// Returns the exclusive bound just past the versions the partial covers, if it has one.
+    fn upper_bound(&self) -> Option<Comparator> {
+        let version = match (self.major, self.minor, self.patch) {
+            (None, _, _) => return None,
+            (Some(major), None, _) => make(major + 1, 0, 0, Some(String::from("0"))),
+            (Some(major), Some(minor), None) => make(major, minor + 1, 0, Some(String::from("0"))),
+            (Some(_), Some(_), Some(_)) => return None,
+        };
+        Some(Comparator::new(Ordering::Less, false, version))
+    }
+
+    fn is_complete(&self) -> bool {
+        self.patch.is_some()
+    }
+
+    /// Returns the comparators for `<=partial`, which includes everything the partial covers.
+    fn at_most(&self) -> Vec<Comparator> {
+        match (self.is_complete(), self.upper_bound()) {
+            (true, _) => vec![Comparator::new(Ordering::Less, true, self.lower_bound().version)],
+            (false, Some(upper)) => vec![upper],
+            (false, None) => vec![],
+        }
+    }
+}
+
+fn make(major: u64, minor: u64, patch: u64, pre_release: Option<String>) -> Semver {
+    Semver {
+        range: None,
+        major: major.to_string(),
+        minor: minor.to_string(),
+        patch: patch.to_string(),
+        pre_release,
+        build_metadata: None,
+    }
+}
+
+fn parse_comparator(token: &str) -> Option<Vec<Comparator>> {
+    let operator_re = Regex::new(r#"^(~>|~|\^|>=|<=|>|<|=)?(.*)$"#).unwrap();
+    let captures = operator_re.captures(token)?;
+    let operator = captures.get(1).map_or("", |operator| operator.as_str());
+    let partial = Partial::parse(&captures[2])?;
+
+    let (major, minor) = (partial.major, partial.minor);
+
+    let comparators = match operator {
+        "" | "=" => match (partial.is_complete(), partial.upper_bound()) {
+            (true, _) => vec![Comparator::new(Ordering::Equal, true, partial.lower_bound().version)],
+            (false, Some(upper)) => vec![partial.lower_bound(), upper],
+            (false, None) => vec![partial.lower_bound()],
+        },
+        ">=" => vec![partial.lower_bound()],
+        "<=" => partial.at_most(),
+        ">" => match (major, minor, partial.is_complete()) {
+            (None, _, _) => vec![Comparator::new(Ordering::Less, false, make(0, 0, 0, None))],
+            (Some(major), None, _) => vec![Comparator::new(Ordering::Greater, true, make(major + 1, 0, 0, None))],
+            (Some(major), Some(minor), false) => {
+                vec![Comparator::new(Ordering::Greater, true, make(major, minor + 1, 0, None))]
+            }
+            (_, _, true) => vec![Comparator::new(Ordering::Greater, false, partial.lower_bound().version)],
+        },
+        "<" => match major {
+            None => vec![Comparator::new(Ordering::Less, false, make(0, 0, 0, None))],
+            Some(_) => {
+                let mut version = partial.lower_bound().version;
+                if !partial.is_complete() {
+                    version.pre_release = Some(String::from("0"));
+                }
+                vec![Comparator::new(Ordering::Less, false, version)]
+            }
+        },
+        "~" | "~>" => {
+            let upper = match (major, minor) {
+                (None, _) => None,
+                (Some(major), None) => Some(make(major + 1, 0, 0, Some(String::from("0")))),
+                (Some(major), Some(minor)) => Some(make(major, minor + 1, 0, Some(String::from("0")))),
+            };
+            with_upper(partial.lower_bound(), upper)
+        }
+        "^" => {
+            let upper = match (major, minor, partial.patch) {
+                (None, _, _) => None,
+                (Some(0), None, _) => Some(make(1, 0, 0, Some(String::from("0")))),
+                (Some(0), Some(0), None) => Some(make(0, 1, 0, Some(String::from("0")))),
+                (Some(0), Some(0), Some(patch)) => Some(make(0, 0, patch + 1, Some(String::from("0")))),
+                (Some(0), Some(minor), _) => Some(make(0, minor + 1, 0, Some(String::from("0")))),
+                (Some(major), _, _) => Some(make(major + 1, 0, 0, Some(String::from("0")))),
+            };
+            with_upper(partial.lower_bound(), upper)
+        }
+        _ => return None,
+    };
+
+    Some(comparators)
+}
+
+fn with_upper(lower: Comparator, upper: Option<Semver>) -> Vec<Comparator> {
+    let mut comparators = vec![lower];
+    if let Some(upper) = upper {
+        comparators.push(Comparator::new(Ordering::Less, false, upper));
+    }
+    comparators
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(version: &str) -> Semver {
+        Semver::from(String::from(version)).unwrap()
+    }
+
+    fn satisfies(range: &str, v: &str) -> bool {
+        Range::parse(range).unwrap().satisfies(&version(v))
+    }
+
+    #[test]
+    fn matches_caret_ranges() {
+        assert!(satisfies("^1.2.3", "1.2.3"));
+        assert!(satisfies("^1.2.3", "1.9.0"));
+        assert!(!satisfies("^1.2.3", "2.0.0"));
+        assert!(!satisfies("^1.2.3", "1.2.2"));
+        assert!(satisfies("^0.2.3", "0.2.9"));
+        assert!(!satisfies("^0.2.3", "0.3.0"));
+        assert!(!satisfies("^0.0.3", "0.0.4"));
+        assert!(satisfies("^1.x", "1.5.0"));
+    }
+
+    #[test]
+    fn matches_tilde_and_x_ranges() {
+        assert!(satisfies("~1.2.3", "1.2.9"));
+        assert!(!satisfies("~1.2.3", "1.3.0"));
+        assert!(satisfies("~1", "1.9.9"));
+        assert!(satisfies("1.x", "1.4.0"));
+        assert!(!satisfies("1.x", "2.0.0"));
+        assert!(satisfies("*", "9.9.9"));
+        assert!(satisfies("", "0.0.1"));
+    }
+
+    #[test]
+    fn matches_comparator_sets() {
+        assert!(satisfies(">=1.2.0 <2", "1.9.9"));
+        assert!(!satisfies(">=1.2.0 <2", "2.0.0"));
+        assert!(satisfies(">= 1.2.0 < 2", "1.2.0"));
+        assert!(satisfies("<1.2", "1.1.9"));
+        assert!(!satisfies("<1.2", "1.2.0"));
+        assert!(satisfies(">1", "2.0.0"));
+        assert!(!satisfies(">1", "1.9.9"));
+        assert!(satisfies("<=1.2", "1.2.9"));
+        assert!(satisfies("1.x || >=3.0.0", "3.1.0"));
+        assert!(!satisfies("1.x || >=3.0.0", "2.1.0"));
+        assert!(satisfies("=1.2.3", "1.2.3"));
+    }
+
+    #[test]
+    fn matches_hyphen_ranges() {
+        assert!(satisfies("1.2.3 - 2.3.4", "2.3.4"));
+        assert!(!satisfies("1.2.3 - 2.3.4", "2.3.5"));
+        assert!(satisfies("1.2 - 2", "2.9.0"));
+        assert!(!satisfies("1.2 - 2", "1.1.0"));
+    }
+
+    #[test]
+    fn excludes_pre_releases_unless_named() {
+        assert!(!satisfies("^1.2.3", "1.3.0-beta.1"));
+        assert!(satisfies("^1.2.3-beta.1", "1.2.3-beta.2"));
+        assert!(Range::parse("^1.2.3").unwrap().satisfies_including_prerelease(&version("1.3.0-beta.1")));
+        assert!(!Range::parse("^1.2.3").unwrap().satisfies_including_prerelease(&version("1.2.3-beta.1")));
+        assert!(!satisfies("^1.2.3-beta.1", "1.2.4-beta.2"));
+    }
+
+    #[test]
+    fn finds_max_satisfying() {
+        let versions = vec![version("1.2.0"), version("1.3.0"), version("2.0.0")];
+        let range = Range::parse("^1.2.0").unwrap();
+        assert_eq!(range.max_satisfying(&versions), Some(&versions[1]));
+    }
+
+    #[test]
+    fn rejects_invalid_ranges() {
+        assert!(Range::parse("latest").is_none());
+        assert!(Range::parse("github:org/repo").is_none());
+    }
+
+    #[test]
+    fn finds_intersecting_ranges() {
+        let intersects = |a: &str, b: &str| Range::parse(a).unwrap().intersects(&Range::parse(b).unwrap());
+        assert!(intersects("18", ">=16"));
+        assert!(intersects("^18.2.0", "18.17.1"));
+        assert!(intersects(">=1.0.0 <=1.0.0", "1.0.0"));
+        assert!(intersects("16 || 20", ">=20"));
+        assert!(!intersects("18", ">=20"));
+        assert!(!intersects("^18.2.0", "18.1.0"));
+        assert!(!intersects(">1.0.0", "<=1.0.0"));
+    }
+}
@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Error;
+use std::path::Path;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+
+use crate::dependency_spec::DependencySpec;
+use crate::npmrc::Npmrc;
+use crate::semver::Semver;
+
+/// The registry document describing every published version of a package.
+#[derive(Debug, Deserialize)]
+pub struct Packument {
+    pub name: String,
+
+    #[serde(rename = "dist-tags", default)]
+    pub dist_tags: HashMap<String, String>,
+
+    #[serde(default)]
+    pub versions: HashMap<String, IgnoredAny>,
+
+    /// Publish times keyed by version, plus `created` and `modified`.
+    #[serde(default)]
+    pub time: HashMap<String, String>,
+}
+
+impl Packument {
+    /// Returns the Packument type from its JSON.
+    pub fn parse(contents: &str) -> Result<Packument, Error> {
+        Ok(serde_json::from_str(contents)?)
+    }
+
+    /// Returns every published version.
+    pub fn versions(&self) -> Vec<Semver> {
+        self.versions
+            .keys()
+            .filter_map(|version| Semver::from(version.to_string()))
+            .collect()
+    }
+
+    /// Returns the version tagged `latest`.
+    pub fn latest(&self) -> Option<Semver> {
+        self.tagged("latest")
+    }
+
+    pub fn tagged(&self, tag: &str) -> Option<Semver> {
+        self.dist_tags
+            .get(tag)
+            .and_then(|version| Semver::from(version.to_string()))
+    }
+
+    /// Returns the version npm would install for the spec: the `latest` version if it is in
+    /// range, otherwise the highest version that is.
+    pub fn wanted(&self, spec: &DependencySpec) -> Option<Semver> {
+        let range = match spec {
+            DependencySpec::Tag(tag) => return self.tagged(tag),
+            DependencySpec::Alias { spec, .. } => return self.wanted(spec),
+            spec => spec.range()?,
+        };
+
+        if let Some(latest) = self.latest() {
+            if range.satisfies(&latest) {
+                return Some(latest);
+            }
+        }
+        let versions = self.versions();
+        range.max_satisfying(&versions).cloned()
+    }
+}
+
+/// Returns the packuments saved in a folder as `<name>.json`, with scoped packages in a folder
+/// for their scope.
+pub fn read_packuments(dir: &Path, names: Vec<String>) -> HashMap<String, Result<Packument, Error>> {
+    names
+        .into_iter()
+        .map(|name| {
+            let path = dir.join(format!("{}.json", name));
+            let packument = fs::read_to_string(path).and_then(|contents| Packument::parse(&contents));
+            (name, packument)
+        })
+        .collect()
+}
+
+/// Fetches packuments from the registries configured in .npmrc.
+pub struct RegistryClient<'a> {
+    npmrc: &'a Npmrc,
+    agent: ureq::Agent,
+}
+
+impl<'a> RegistryClient<'a> {
+    pub fn new(npmrc: &'a Npmrc, timeout: Duration) -> Self {
+        RegistryClient {
+            npmrc,
+            agent: ureq::AgentBuilder::new().timeout(timeout).build(),
+        }
+    }
+
+    /// Returns the packument for a package from the registry configured for its scope.
+    pub fn fetch(&self, name: &str) -> Result<Packument, Error> {
+        let registry = self.npmrc.registry_for(name);
+        let url = format!("{}{}", registry, name.replace('/', "%2F"));
+
+        let mut request = self.agent.get(&url).set("Accept", "application/json");
+        if let Some(token) = self.npmrc.auth_token(&registry) {
+            request = request.set("Authorization", &format!("Bearer {}", token));
+        }
+
+        let response = request.call().map_err(|err| Error::other(err.to_string()))?;
+        Packument::parse(&response.into_string()?)
+    }
+
+    /// Returns the packuments for every name, making at most `concurrency` requests at once.
+    pub fn fetch_all(
+        &self,
+        names: Vec<String>,
+        concurrency: usize,
+    ) -> HashMap<String, Result<Packument, Error>> {
+        let queue = Mutex::new(names);
+        let results = Mutex::new(HashMap::new());
+
+        thread::scope(|scope| {
+            for _ in 0..concurrency.max(1) {
+                scope.spawn(|| loop {
+                    let name = match queue.lock().unwrap().pop() {
+                        Some(name) => name,
+                        None => break,
+                    };
+                    let packument = self.fetch(&name);
+                    results.lock().unwrap().insert(name, packument);
+                });
+            }
+        });
+
+        results.into_inner().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    const PACKUMENT: &str = r#"{
+        "name": "@scope/a",
+        "dist-tags": { "latest": "1.5.0", "next": "2.0.0-beta.1" },
+        "versions": { "1.0.0": {}, "1.5.0": {}, "1.6.0": {}, "2.0.0-beta.1": {} },
+        "time": { "1.0.0": "2020-01-01T00:00:00.000Z" }
+    }"#;
+
+    /// Serves the packument to any request, recording the request lines and headers.
+    fn stub_registry(requests: usize) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+
+        let handle = thread::spawn(move || {
+            let mut received = Vec::new();
+            for stream in listener.incoming().take(requests) {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    received.push(line.trim().to_string());
+                }
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    PACKUMENT.len(),
+                    PACKUMENT
+                )
+                .unwrap();
+            }
+            received
+        });
+
+        (url, handle)
+    }
+
+    #[test]
+    fn fetches_from_the_scope_registry_with_a_token() {
+        let (url, handle) = stub_registry(1);
+        let host = &url["http:".len()..];
+        let npmrc = Npmrc::parse(&format!(
+            "registry=http://127.0.0.1:1/\n@scope:registry={}\n{}:_authToken=secret\n",
+            url, host
+        ));
+        let client = RegistryClient::new(&npmrc, Duration::from_secs(5));
+
+        let packument = client.fetch("@scope/a").unwrap();
+        assert_eq!(packument.name, "@scope/a");
+
+        let received = handle.join().unwrap();
+        assert_eq!(received[0], "GET /@scope%2Fa HTTP/1.1");
+        assert!(received.iter().any(|line| line == "Authorization: Bearer secret"));
+    }
+
+    #[test]
+    fn fetches_concurrently() {
+        let (url, handle) = stub_registry(3);
+        let npmrc = Npmrc::parse(&format!("registry={}\n", url));
+        let client = RegistryClient::new(&npmrc, Duration::from_secs(5));
+
+        let names = vec![String::from("a"), String::from("b"), String::from("c")];
+        let packuments = client.fetch_all(names, 2);
+        assert_eq!(packuments.len(), 3);
+        assert!(packuments.values().all(|packument| packument.is_ok()));
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn finds_wanted_versions() {
+        let packument = Packument::parse(PACKUMENT).unwrap();
+        let wanted = |spec: &str| packument.wanted(&DependencySpec::from(spec)).map(|v| v.to_string());
+
+        assert_eq!(packument.latest().unwrap().to_string(), "1.5.0");
+        assert_eq!(wanted("^1.0.0").as_deref(), Some("1.5.0"));
+        assert_eq!(wanted("~1.6.0").as_deref(), Some("1.6.0"));
+        assert_eq!(wanted("next").as_deref(), Some("2.0.0-beta.1"));
+        assert_eq!(wanted("npm:@scope/a@1.0.0").as_deref(), Some("1.0.0"));
+        assert_eq!(wanted("^3.0.0"), None);
+    }
+}
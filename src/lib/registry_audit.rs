@@ -0,0 +1,261 @@
+use std::fs;
+use std::io::Error;
+use std::path::Path;
+
+use prettytable::{color, Attr, Row};
+
+use crate::lockfile::Lockfile;
+use crate::node_module::{new_cell, PrintTable};
+use crate::node_module::standard_module::StandardModule;
+use crate::npmrc::{get_scope, strip_scheme, Npmrc};
+use crate::source::is_public_registry;
+
+/// A package that may have been fetched from somewhere other than where it should have been.
+#[derive(Debug, PartialEq)]
+pub struct RegistryFinding {
+    pub name: String,
+    pub version: String,
+    pub resolved: String,
+    pub expected: String,
+    pub issue: RegistryIssue,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RegistryIssue {
+    /// A scoped package resolved from a registry other than the one mapped to its scope.
+    WrongRegistry,
+    /// An unscoped package sharing its name with an internal package, resolved from somewhere
+    /// other than a private or approved registry.
+    InternalName,
+    /// A package that should come from a particular registry but has no `resolved` URL to check.
+    Unverifiable,
+}
+
+/// A package name, version and `resolved` URL to be audited.
+pub struct ResolvedPackage {
+    pub name: String,
+    pub version: String,
+    pub resolved: Option<String>,
+}
+
+impl ResolvedPackage {
+    /// Returns every package in the lockfile, including nested ones.
+    pub fn from_lockfile(lockfile: &Lockfile) -> Vec<Self> {
+        lockfile
+            .packages
+            .iter()
+            .map(|(path, locked)| ResolvedPackage {
+                name: get_name_from_lock_path(path).to_string(),
+                version: locked.version.clone().unwrap_or_default(),
+                resolved: locked.resolved.clone(),
+            })
+            .collect()
+    }
+
+    /// Returns the top-level installed packages, using the `_resolved` field written by npm.
+    pub fn from_modules(dependencies: &[StandardModule]) -> Vec<Self> {
+        dependencies
+            .iter()
+            .map(|dependency| ResolvedPackage {
+                name: dependency.name.clone(),
+                version: dependency
+                    .version
+                    .as_ref()
+                    .map(|version| version.to_string())
+                    .unwrap_or_default(),
+                resolved: dependency.resolved.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Returns the package names in an allow-list file, one per line, ignoring `#` comments.
+pub fn read_internal_packages(path: &Path) -> Result<Vec<String>, Error> {
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/// Returns the packages resolved from an unexpected registry, sorted by name. Internal names
+/// must come from a registry configured in the .npmrc or one of the `approved_registries` hosts,
+/// never from a public registry.
+pub fn audit(
+    packages: Vec<ResolvedPackage>,
+    npmrc: &Npmrc,
+    internal_packages: &[String],
+    approved_registries: &[String],
+) -> Vec<RegistryFinding> {
+    let mut findings = Vec::new();
+    let private_registries = get_private_registries(npmrc, approved_registries);
+
+    for package in packages {
+        let resolved = package.resolved.unwrap_or_default();
+
+        let expected = match get_scope(&package.name) {
+            Some(scope) => npmrc
+                .scope_registry(scope)
+                .map(|expected| (RegistryIssue::WrongRegistry, vec![expected])),
+            None if internal_packages.contains(&package.name) => {
+                Some((RegistryIssue::InternalName, private_registries.clone()))
+            }
+            None => None,
+        };
+        let issue = match expected {
+            Some((_, expected)) if resolved.is_empty() => Some((RegistryIssue::Unverifiable, expected)),
+            Some((issue, expected)) if !expected.iter().any(|registry| is_from_registry(&resolved, registry)) => {
+                Some((issue, expected))
+            }
+            _ => None,
+        };
+
+        if let Some((issue, expected)) = issue {
+            findings.push(RegistryFinding {
+                name: package.name,
+                version: package.version,
+                resolved,
+                expected: match expected.is_empty() {
+                    true => String::from("a private registry"),
+                    false => expected.join(" or "),
+                },
+                issue,
+            });
+        }
+    }
+
+    findings.sort_by(|a, b| a.name.cmp(&b.name).then(a.version.cmp(&b.version)));
+    findings.dedup();
+    findings
+}
+
+/// Returns the registries an internal package may come from: the default and scope registries
+/// in the .npmrc and the approved hosts, except for public ones where anyone can publish the name.
+fn get_private_registries(npmrc: &Npmrc, approved_registries: &[String]) -> Vec<String> {
+    let mut registries = npmrc.scope_registries();
+    registries.push(npmrc.registry());
+    registries.extend(approved_registries.iter().map(|host| format!("https://{}/", host)));
+    registries.retain(|registry| !is_public_registry(strip_scheme(registry).split('/').next().unwrap_or("")));
+    registries.sort();
+    registries.dedup();
+    registries
+}
+
+fn is_from_registry(resolved: &str, registry: &str) -> bool {
+    strip_scheme(resolved).starts_with(strip_scheme(registry))
+}
+
+/// Returns the package name from a lockfile path such as `node_modules/a/node_modules/@s/b`.
+pub fn get_name_from_lock_path(path: &str) -> &str {
+    match path.rfind("node_modules/") {
+        Some(i) => &path[i + "node_modules/".len()..],
+        None => path,
+    }
+}
+
+impl PrintTable for RegistryFinding {
+    fn table_row(&self) -> Row {
+        let issue = match self.issue {
+            RegistryIssue::WrongRegistry => new_cell("wrong registry").with_style(Attr::ForegroundColor(color::RED)),
+            RegistryIssue::InternalName => new_cell("internal name").with_style(Attr::ForegroundColor(color::RED)),
+            RegistryIssue::Unverifiable => new_cell("unverifiable").with_style(Attr::ForegroundColor(color::YELLOW)),
+        };
+        let resolved = match self.resolved.is_empty() {
+            true => "???",
+            false => &self.resolved,
+        };
+        Row::new(vec![
+            new_cell(&self.name),
+            new_cell(&self.version),
+            issue,
+            new_cell(resolved),
+            new_cell(&self.expected),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(name: &str, resolved: &str) -> ResolvedPackage {
+        ResolvedPackage {
+            name: name.to_string(),
+            version: String::from("1.0.0"),
+            resolved: Some(resolved.to_string()),
+        }
+    }
+
+    #[test]
+    fn flags_scoped_packages_from_the_wrong_registry() {
+        let npmrc = Npmrc::parse("@ourco:registry=http://localhost:4873/\n");
+        let findings = audit(
+            vec![
+                package("@ourco/ui", "https://registry.npmjs.org/@ourco/ui/-/ui-1.0.0.tgz"),
+                package("@ourco/api", "http://localhost:4873/@ourco/api/-/api-1.0.0.tgz"),
+                package("@other/ui", "https://registry.npmjs.org/@other/ui/-/ui-1.0.0.tgz"),
+                package("@ourco/cli", ""),
+            ],
+            &npmrc,
+            &[],
+            &[],
+        );
+
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].name, "@ourco/cli");
+        assert_eq!(findings[0].issue, RegistryIssue::Unverifiable);
+        assert_eq!(findings[1].name, "@ourco/ui");
+        assert_eq!(findings[1].issue, RegistryIssue::WrongRegistry);
+        assert_eq!(findings[1].expected, "http://localhost:4873/");
+    }
+
+    #[test]
+    fn flags_unscoped_internal_names() {
+        let npmrc = Npmrc::parse("registry=https://npm.ourco.com/\n");
+        let findings = audit(
+            vec![
+                package("ourco-utils", "https://registry.npmjs.org/ourco-utils/-/ourco-utils-1.0.0.tgz"),
+                package("ourco-config", "https://npm.ourco.com/ourco-config/-/ourco-config-1.0.0.tgz"),
+                package("left-pad", "https://registry.npmjs.org/left-pad/-/left-pad-1.0.0.tgz"),
+            ],
+            &npmrc,
+            &[String::from("ourco-utils"), String::from("ourco-config")],
+            &[],
+        );
+
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].name, "ourco-utils");
+        assert_eq!(findings[0].issue, RegistryIssue::InternalName);
+    }
+
+    #[test]
+    fn flags_internal_names_from_the_public_registry() {
+        let internal_packages = [String::from("ourco-utils"), String::from("ourco-config"), String::from("ourco-cli")];
+        let packages = || {
+            vec![
+                package("ourco-utils", "https://registry.npmjs.org/ourco-utils/-/ourco-utils-1.0.0.tgz"),
+                package("ourco-config", "http://localhost:4873/ourco-config/-/ourco-config-1.0.0.tgz"),
+                package("ourco-cli", "https://npm.ourco.com/ourco-cli/-/ourco-cli-1.0.0.tgz"),
+            ]
+        };
+
+        let findings = audit(packages(), &Npmrc::parse(""), &internal_packages, &[]);
+        assert_eq!(findings.len(), 3);
+        assert!(findings.iter().all(|finding| finding.issue == RegistryIssue::InternalName));
+        assert_eq!(findings[0].expected, "a private registry");
+
+        let npmrc = Npmrc::parse("@ourco:registry=http://localhost:4873/\n");
+        let approved = [String::from("npm.ourco.com"), String::from("registry.npmjs.org")];
+        let findings = audit(packages(), &npmrc, &internal_packages, &approved);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].name, "ourco-utils");
+        assert_eq!(findings[0].expected, "http://localhost:4873/ or https://npm.ourco.com/");
+    }
+
+    #[test]
+    fn reads_names_from_lock_paths() {
+        assert_eq!(get_name_from_lock_path("node_modules/a"), "a");
+        assert_eq!(get_name_from_lock_path("node_modules/a/node_modules/@s/b"), "@s/b");
+    }
+}
@@ -0,0 +1,195 @@
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+
+use crate::node_module::standard_module::StandardModule;
+use crate::semver::Semver;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Major,
+    Minor,
+    Patch,
+    Downgrade,
+}
+
+impl ChangeKind {
+    fn heading(&self) -> &'static str {
+        match self {
+            ChangeKind::Added => "Added",
+            ChangeKind::Removed => "Removed",
+            ChangeKind::Major => "Major upgrades",
+            ChangeKind::Minor => "Minor upgrades",
+            ChangeKind::Patch => "Patch upgrades",
+            ChangeKind::Downgrade => "Downgrades",
+        }
+    }
+}
+
+/// A package whose version differs between two sets of dependencies.
+#[derive(Debug)]
+pub struct DependencyChange<'a> {
+    pub name: &'a str,
+    pub old: Option<&'a Semver>,
+    pub new: Option<&'a Semver>,
+    pub kind: ChangeKind,
+}
+
+impl<'a> DependencyChange<'a> {
+    fn describe(&self) -> String {
+        match (self.old, self.new) {
+            (Some(old), Some(new)) => format!("{} → {}", old, new),
+            (Some(version), None) | (None, Some(version)) => version.to_string(),
+            (None, None) => String::new(),
+        }
+    }
+}
+
+/// Returns the packages added, removed, upgraded or downgraded between the two sets, grouped by
+/// kind and sorted by name within each group.
+pub fn get_changes<'a>(old: &'a [StandardModule], new: &'a [StandardModule]) -> Vec<DependencyChange<'a>> {
+    let version = |dependencies: &'a [StandardModule], name: &str| {
+        dependencies
+            .iter()
+            .find(|dependency| dependency.name == name)
+            .map(|dependency| dependency.version.as_ref())
+    };
+    let names: BTreeSet<&str> = old
+        .iter()
+        .chain(new.iter())
+        .map(|dependency| dependency.name.as_str())
+        .collect();
+
+    let mut changes: Vec<DependencyChange> = names
+        .into_iter()
+        .filter_map(|name| {
+            let (old, new) = (version(old, name), version(new, name));
+            let kind = match (old, new) {
+                (None, Some(_)) => ChangeKind::Added,
+                (Some(_), None) => ChangeKind::Removed,
+                (Some(Some(old)), Some(Some(new))) => get_upgrade_kind(old, new)?,
+                _ => return None,
+            };
+            Some(DependencyChange {
+                name,
+                old: old.flatten(),
+                new: new.flatten(),
+                kind,
+            })
+        })
+        .collect();
+    changes.sort_by_key(|change| change.kind);
+    changes
+}
+
+/// Returns the kind of change from one version to another, or None if they are the same.
+fn get_upgrade_kind(old: &Semver, new: &Semver) -> Option<ChangeKind> {
+    match new.cmp(old) {
+        Ordering::Equal => None,
+        Ordering::Less => Some(ChangeKind::Downgrade),
+        Ordering::Greater => {
+            let (old, new) = (old.numbers(), new.numbers());
+            Some(match (new.0 != old.0, new.1 != old.1) {
+                (true, _) => ChangeKind::Major,
+                (false, true) => ChangeKind::Minor,
+                (false, false) => ChangeKind::Patch,
+            })
+        }
+    }
+}
+
+/// Returns the changes grouped under a heading for each kind.
+fn get_groups<'a, 'b>(changes: &'b [DependencyChange<'a>]) -> Vec<(ChangeKind, &'b [DependencyChange<'a>])> {
+    changes
+        .chunk_by(|a, b| a.kind == b.kind)
+        .map(|group| (group[0].kind, group))
+        .collect()
+}
+
+pub fn to_text(changes: &[DependencyChange]) -> String {
+    let mut document = String::from("Dependency changes\n");
+    if changes.is_empty() {
+        document.push_str("\nNo dependency changes.\n");
+    }
+    for (kind, group) in get_groups(changes) {
+        document.push_str(&format!("\n{}:\n", kind.heading()));
+        for change in group {
+            document.push_str(&format!("  {} {}\n", change.name, change.describe()));
+        }
+    }
+    document
+}
+
+pub fn to_markdown(changes: &[DependencyChange]) -> String {
+    let mut document = String::from("## Dependency changes\n");
+    if changes.is_empty() {
+        document.push_str("\nNo dependency changes.\n");
+    }
+    for (kind, group) in get_groups(changes) {
+        document.push_str(&format!("\n### {}\n\n", kind.heading()));
+        for change in group {
+            document.push_str(&format!("- `{}` {}\n", change.name, change.describe()));
+        }
+    }
+    document
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dependencies(versions: &[(&str, &str)]) -> Vec<StandardModule> {
+        versions
+            .iter()
+            .map(|(name, version)| StandardModule {
+                name: name.to_string(),
+                version: Semver::from(version.to_string()),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn groups_changes() {
+        let old = dependencies(&[
+            ("a", "1.0.0"),
+            ("b", "1.0.0"),
+            ("c", "1.0.0"),
+            ("d", "2.0.0"),
+            ("e", "1.0.0"),
+            ("f", "1.0.0"),
+            ("same", "1.0.0"),
+        ]);
+        let new = dependencies(&[
+            ("a", "2.0.0"),
+            ("b", "1.1.0"),
+            ("c", "1.0.1"),
+            ("d", "1.9.0"),
+            ("f", "1.0.1-beta.1"),
+            ("g", "0.1.0"),
+            ("same", "1.0.0"),
+        ]);
+
+        let changes = get_changes(&old, &new);
+        let kinds: Vec<(&str, ChangeKind)> = changes.iter().map(|change| (change.name, change.kind)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("g", ChangeKind::Added),
+                ("e", ChangeKind::Removed),
+                ("a", ChangeKind::Major),
+                ("b", ChangeKind::Minor),
+                ("c", ChangeKind::Patch),
+                ("f", ChangeKind::Patch),
+                ("d", ChangeKind::Downgrade),
+            ]
+        );
+
+        let markdown = to_markdown(&changes);
+        assert!(markdown.contains("### Major upgrades\n\n- `a` 1.0.0 → 2.0.0\n"));
+        assert!(markdown.contains("### Added\n\n- `g` 0.1.0\n"));
+        assert!(to_text(&changes).contains("\nDowngrades:\n  d 2.0.0 → 1.9.0\n"));
+        assert_eq!(to_text(&[]), "Dependency changes\n\nNo dependency changes.\n");
+    }
+}
@@ -1,5 +1,6 @@
 use regex::Regex;
 use std::cmp::Ordering;
+use std::fmt;
 
 #[cfg(not(test))]
 use crate::cli::Cli;
@@ -7,7 +8,7 @@ use crate::cli::Cli;
 #[cfg(test)]
 use tests::Cli;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Semver {
     pub range: Option<String>,
     pub major: String,
@@ -30,11 +31,7 @@ impl Semver {
                         .get(num)
                         .map_or(String::new(), |v| v.as_str().to_string())
                 };
-                let get_option = |num| {
-                    captures
-                        .get(num)
-                        .map_or(None, |v| Some(v.as_str().to_string()))
-                };
+                let get_option = |num| captures.get(num).map(|v| v.as_str().to_string());
 
                 Some(Semver {
                     range: get_option(1),
@@ -49,7 +46,21 @@ impl Semver {
         }
     }
 
-    pub fn to_string(&self) -> String {
+    /// Returns true if the versions have the same precedence, ignoring any range and build
+    /// metadata.
+    pub fn same_version(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+
+    /// Returns the major, minor and patch numbers.
+    pub fn numbers(&self) -> (u64, u64, u64) {
+        let number = |part: &str| part.parse::<u64>().unwrap_or(0);
+        (number(&self.major), number(&self.minor), number(&self.patch))
+    }
+}
+
+impl fmt::Display for Semver {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut version = format!("{}.{}.{}", self.major, self.minor, self.patch);
         if let Some(pre_release) = &self.pre_release {
             version = format!("{}-{}", version, pre_release);
@@ -65,17 +76,20 @@ impl Semver {
                 version = format!("{}+{}", version, build_metadata);
             }
         }
-        version
+        write!(f, "{}", version)
     }
 }
 
-fn compare_parts(parts: &Vec<&str>, other_parts: &Vec<&str>) -> Ordering {
+fn compare_parts(parts: &[&str], other_parts: &[&str]) -> Ordering {
     for (i, part) in parts.iter().enumerate() {
-        let other_part = other_parts[i];
+        let other_part = match other_parts.get(i) {
+            Some(other_part) => *other_part,
+            None => return Ordering::Greater,
+        };
         let ordering: Ordering;
 
-        if let Ok(int) = part.parse::<u16>() {
-            if let Ok(other_int) = other_part.parse::<u16>() {
+        if let Ok(int) = part.parse::<u64>() {
+            if let Ok(other_int) = other_part.parse::<u64>() {
                 ordering = int.cmp(&other_int);
             } else {
                 ordering = part.cmp(&other_part);
@@ -89,14 +103,14 @@ fn compare_parts(parts: &Vec<&str>, other_parts: &Vec<&str>) -> Ordering {
             return ordering;
         }
     }
-    Ordering::Equal
+    parts.len().cmp(&other_parts.len())
 }
 
 impl Ord for Semver {
     fn cmp(&self, other: &Self) -> Ordering {
         let compared = compare_parts(
-            &vec![&self.major, &self.minor, &self.patch],
-            &vec![&other.major, &other.minor, &other.patch],
+            &[self.major.as_str(), &self.minor, &self.patch],
+            &[other.major.as_str(), &other.minor, &other.patch],
         );
 
         if let Ordering::Equal = compared {
@@ -104,7 +118,9 @@ impl Ord for Semver {
                 Some(tags) => match &other.pre_release {
                     None => Ordering::Less,
                     Some(other_tags) => {
-                        compare_parts(&tags.split('.').collect(), &other_tags.split('.').collect())
+                        let parts: Vec<&str> = tags.split('.').collect();
+                        let other_parts: Vec<&str> = other_tags.split('.').collect();
+                        compare_parts(&parts, &other_parts)
                     }
                 },
                 None => match other.pre_release {
@@ -113,7 +129,7 @@ impl Ord for Semver {
                 },
             };
         }
-        return compared;
+        compared
     }
 }
 
@@ -167,10 +183,12 @@ mod tests {
     #[test]
     fn handles_git_string() {
         let non_semver = Semver::from(String::from("git+ssh://git@github.com/my-package.git#semver:~6.1.5")).unwrap();
-        assert_eq!(non_semver, make("6.1.5"));
+        assert!(non_semver.same_version(&make("6.1.5")));
+        assert_ne!(non_semver, make("6.1.5"));
     }
 
     #[test]
+    #[allow(clippy::assertions_on_constants)]
     fn creates_complex_semver() {
         let semver = make("8.1.2-alpha.0.1+1.2.3");
         assert_eq!(semver.major, "8");
@@ -220,6 +238,9 @@ mod tests {
         assert!(main < make("8.5.0-alpha.0.1"));
         assert!(main < make("9.0.0-alpha.0.1"));
 
+        assert!(make("8.4.12-alpha") < make("8.4.12-alpha.1"));
+        assert!(make("8.4.12-alpha.1") > make("8.4.12-alpha"));
+
         let main = make("8.4.12-alpha.1.1");
         assert!(main < make("8.4.12-beta.0.1"));
         assert!(main > make("8.4.12-alpha.0.1"));
@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Error;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::lockfile::Lockfile;
+use crate::node_module::standard_module::StandardModule;
+use crate::node_module::{is_production, DepType};
+use crate::pjson_detail::PjsonDetail;
+use crate::semver::Semver;
+
+/// An installed package as recorded in a snapshot.
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct SnapshotPackage {
+    pub name: String,
+    pub version: Option<String>,
+    /// The package folder, relative to the project.
+    pub path: String,
+    pub dep_type: String,
+    /// The spec from the package.json, for packages the project declares.
+    pub declared: Option<String>,
+    pub resolved: Option<String>,
+    pub source: String,
+    /// True for packages only needed in development. None when the snapshot was saved without a
+    /// lockfile to tell, or before this was recorded.
+    #[serde(default)]
+    pub dev: Option<bool>,
+}
+
+/// The installed packages of a project at one point in time, written by `snapshot save`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Snapshot {
+    pub name: String,
+    pub version: String,
+    pub packages: Vec<SnapshotPackage>,
+}
+
+impl Snapshot {
+    pub fn from(
+        app_pjson: &PjsonDetail,
+        project: &Path,
+        dependencies: &[StandardModule],
+        lockfile: Option<&Lockfile>,
+    ) -> Self {
+        let mut packages: Vec<SnapshotPackage> = dependencies
+            .iter()
+            .map(|dependency| SnapshotPackage {
+                name: dependency.name.clone(),
+                version: dependency.version.as_ref().map(|version| version.to_string()),
+                path: dependency
+                    .path
+                    .strip_prefix(project)
+                    .unwrap_or(&dependency.path)
+                    .display()
+                    .to_string(),
+                dep_type: dependency.dep_type.name().to_string(),
+                declared: match dependency.dep_type {
+                    DepType::Dependency(_) => get_declared(&dependency.name, &app_pjson.dependencies),
+                    DepType::DevDependency(_) => get_declared(&dependency.name, &app_pjson.dev_dependencies),
+                    DepType::ChildDependency => None,
+                },
+                resolved: dependency.resolved.clone(),
+                source: dependency.source().to_string(),
+                dev: match dependency.dep_type {
+                    DepType::Dependency(_) => Some(false),
+                    DepType::DevDependency(_) => Some(true),
+                    DepType::ChildDependency => lockfile.map(|lockfile| !is_production(dependency, Some(lockfile))),
+                },
+            })
+            .collect();
+        packages.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Snapshot {
+            name: app_pjson.name.clone(),
+            version: app_pjson.version.clone(),
+            packages,
+        }
+    }
+
+    /// Returns the Snapshot type from a file written by `write`.
+    pub fn read(path: &Path) -> Result<Self, Error> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, Error> {
+        Ok(serde_json::from_str(contents)?)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), Error> {
+        fs::write(path, serde_json::to_string_pretty(self)? + "\n")
+    }
+
+    /// Returns the recorded packages as they would be read from node_modules, sorted by name.
+    pub fn modules(&self, project: &Path) -> Vec<StandardModule> {
+        self.packages
+            .iter()
+            .map(|package| StandardModule {
+                name: package.name.clone(),
+                path: project.join(&package.path),
+                version: package.version.clone().and_then(Semver::from),
+                dep_type: DepType::from(&package.dep_type, package.declared.as_deref()),
+                resolved: package.resolved.clone(),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    /// Returns the recorded packages needed in production, sorted by name, or None if the
+    /// snapshot doesn't record which packages are only needed in development.
+    pub fn production_modules(&self, project: &Path) -> Option<Vec<StandardModule>> {
+        let mut modules = Vec::new();
+        for (package, module) in self.packages.iter().zip(self.modules(project)) {
+            if !package.dev? {
+                modules.push(module);
+            }
+        }
+        Some(modules)
+    }
+
+    /// Returns the recorded and current sources of each package that is now installed from
+    /// somewhere else.
+    pub fn changed_sources<'a>(&'a self, dependencies: &[StandardModule]) -> Vec<(&'a str, &'a str, String)> {
+        self.packages
+            .iter()
+            .filter_map(|package| {
+                let current = dependencies.iter().find(|dependency| dependency.name == package.name)?;
+                let source = current.source().to_string();
+                match source != package.source {
+                    true => Some((package.name.as_str(), package.source.as_str(), source)),
+                    false => None,
+                }
+            })
+            .collect()
+    }
+}
+
+fn get_declared(name: &str, declared: &Option<HashMap<String, String>>) -> Option<String> {
+    declared.as_ref()?.get(name).cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dependency_spec::DependencySpec;
+    use std::path::PathBuf;
+
+    #[test]
+    fn round_trips_installed_packages() {
+        let project = PathBuf::from("/src/app");
+        let app_pjson: PjsonDetail = serde_json::from_str(
+            r#"{ "name": "app", "version": "1.0.0", "dependencies": { "a": "npm:b@^1.0.0" } }"#,
+        )
+        .unwrap();
+        let installed = vec![
+            StandardModule {
+                name: String::from("c"),
+                path: project.join("node_modules/c"),
+                version: Semver::from(String::from("2.0.0")),
+                resolved: Some(String::from("https://registry.npmjs.org/c/-/c-2.0.0.tgz")),
+                ..Default::default()
+            },
+            StandardModule {
+                name: String::from("a"),
+                path: project.join("node_modules/a"),
+                version: Semver::from(String::from("1.2.0")),
+                dep_type: DepType::Dependency(DependencySpec::from("npm:b@^1.0.0")),
+                ..Default::default()
+            },
+        ];
+
+        let snapshot = Snapshot::from(&app_pjson, &project, &installed, None);
+        assert_eq!(snapshot.packages[0].path, "node_modules/a");
+        assert_eq!(snapshot.packages[0].declared.as_deref(), Some("npm:b@^1.0.0"));
+        assert_eq!(snapshot.packages[1].source, "registry: registry.npmjs.org");
+
+        let modules = snapshot.modules(&project);
+        assert_eq!(modules[0].name, "a");
+        assert!(matches!(&modules[0].dep_type, DepType::Dependency(DependencySpec::Alias { .. })));
+        assert_eq!(modules[1].path, project.join("node_modules/c"));
+        assert!(snapshot.changed_sources(&modules).is_empty());
+
+        let moved = vec![StandardModule {
+            name: String::from("c"),
+            resolved: Some(String::from("git+https://github.com/me/c.git#abc")),
+            ..Default::default()
+        }];
+        let changed = snapshot.changed_sources(&moved);
+        assert_eq!(changed, vec![("c", "registry: registry.npmjs.org", String::from("git: github.com/me/c#abc"))]);
+    }
+
+    #[test]
+    fn records_dev_packages() {
+        let project = PathBuf::from("/src/app");
+        let app_pjson: PjsonDetail = serde_json::from_str(r#"{ "name": "app", "version": "1.0.0" }"#).unwrap();
+        let installed = vec![
+            StandardModule {
+                name: String::from("a"),
+                ..Default::default()
+            },
+            StandardModule {
+                name: String::from("b"),
+                ..Default::default()
+            },
+        ];
+        let lockfile = Lockfile::parse(
+            r#"{ "packages": { "node_modules/a": { "version": "1.0.0" }, "node_modules/b": { "version": "1.0.0", "dev": true } } }"#,
+        )
+        .unwrap();
+
+        let snapshot = Snapshot::from(&app_pjson, &project, &installed, Some(&lockfile));
+        let production = snapshot.production_modules(&project).unwrap();
+        assert_eq!(production.len(), 1);
+        assert_eq!(production[0].name, "a");
+
+        let unknown = Snapshot::from(&app_pjson, &project, &installed, None);
+        assert!(unknown.production_modules(&project).is_none());
+        let saved_before = Snapshot::parse(
+            r#"{ "name": "app", "version": "1.0.0", "packages": [
+                { "name": "a", "path": "node_modules/a", "dep_type": "child dependency", "source": "" }
+            ] }"#,
+        )
+        .unwrap();
+        assert!(saved_before.production_modules(&project).is_none());
+    }
+}
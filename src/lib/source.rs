@@ -0,0 +1,142 @@
+use std::fmt;
+
+use crate::cli::Cli;
+use crate::dependency_spec::DependencySpec;
+use crate::link::Link;
+
+const PUBLIC_REGISTRIES: [&str; 3] = [
+    "registry.npmjs.org",
+    "registry.npmjs.com",
+    "registry.yarnpkg.com",
+];
+
+/// Where an installed package was fetched from.
+#[derive(Debug, PartialEq)]
+pub enum Source {
+    /// A public npm registry, or one given with --approved-registry.
+    ApprovedRegistry(String),
+    PrivateRegistry(String),
+    Git { repo: String, commit: Option<String> },
+    Tarball(String),
+    Local(String),
+    Unknown,
+}
+
+impl Source {
+    /// Returns the Source for a package from its `resolved` URL, or from its link target when
+    /// the package is symlinked into node_modules.
+    pub fn from(resolved: Option<&str>, link: Option<&Link>) -> Self {
+        Self::classify(resolved, link, &Cli::get().approved_registries)
+    }
+
+    /// Returns the Source for a package, treating the `approved` registry hosts like the public
+    /// ones.
+    pub fn classify(resolved: Option<&str>, link: Option<&Link>, approved: &[String]) -> Self {
+        if let Some(link) = link {
+            return Source::Local(link.target.display().to_string());
+        }
+        let resolved = match resolved {
+            Some(resolved) => resolved,
+            None => return Source::Unknown,
+        };
+
+        match DependencySpec::from(resolved) {
+            DependencySpec::Git { repo, committish } => Source::Git {
+                repo,
+                commit: committish,
+            },
+            DependencySpec::File(path) | DependencySpec::Link(path) => Source::Local(path),
+            DependencySpec::Tarball(url) => match registry_host(&url) {
+                Some(host) if is_public_registry(&host) || approved.contains(&host) => {
+                    Source::ApprovedRegistry(host)
+                }
+                Some(host) => Source::PrivateRegistry(host),
+                None => Source::Tarball(url),
+            },
+            _ => Source::Unknown,
+        }
+    }
+
+    /// Returns the registry host the package came from, if it came from a registry.
+    pub fn registry(&self) -> Option<&str> {
+        match self {
+            Source::ApprovedRegistry(host) | Source::PrivateRegistry(host) => Some(host),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Source::ApprovedRegistry(host) => write!(f, "registry: {}", host),
+            Source::PrivateRegistry(host) => write!(f, "private: {}", host),
+            Source::Git { repo, commit } => match commit {
+                Some(commit) => write!(f, "git: {}#{}", repo, commit),
+                None => write!(f, "git: {}", repo),
+            },
+            Source::Tarball(url) => write!(f, "tarball: {}", url),
+            Source::Local(path) => write!(f, "local: {}", path),
+            Source::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Registry tarballs live under `<registry>/<name>/-/<file>.tgz`, which is how they are told
+/// apart from arbitrary tarball URLs.
+/// Returns true for the hosts of the public registries, where anyone can publish a package.
+pub fn is_public_registry(host: &str) -> bool {
+    PUBLIC_REGISTRIES.contains(&host)
+}
+
+fn registry_host(url: &str) -> Option<String> {
+    if !url.contains("/-/") {
+        return None;
+    }
+    let without_scheme = &url[url.find("://")? + 3..];
+    let host = without_scheme.split('/').next()?;
+    let host = host.rsplit('@').next()?;
+    Some(host.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(resolved: &str) -> Source {
+        Source::classify(Some(resolved), None, &[])
+    }
+
+    #[test]
+    fn classifies_registries() {
+        assert_eq!(
+            source("https://registry.npmjs.org/@scope/a/-/a-1.0.0.tgz"),
+            Source::ApprovedRegistry(String::from("registry.npmjs.org"))
+        );
+        assert_eq!(
+            source("https://npm.ourco.com/a/-/a-1.0.0.tgz"),
+            Source::PrivateRegistry(String::from("npm.ourco.com"))
+        );
+        assert_eq!(
+            Source::classify(Some("https://npm.ourco.com/a/-/a-1.0.0.tgz"), None, &[String::from("npm.ourco.com")]),
+            Source::ApprovedRegistry(String::from("npm.ourco.com"))
+        );
+    }
+
+    #[test]
+    fn classifies_other_sources() {
+        assert_eq!(
+            source("git+ssh://git@github.com/org/repo.git#0123abc"),
+            Source::Git {
+                repo: String::from("github.com/org/repo"),
+                commit: Some(String::from("0123abc")),
+            }
+        );
+        assert_eq!(
+            source("https://example.com/a.tgz"),
+            Source::Tarball(String::from("https://example.com/a.tgz"))
+        );
+        assert_eq!(source("file:../a"), Source::Local(String::from("../a")));
+        assert_eq!(Source::classify(None, None, &[]), Source::Unknown);
+    }
+}
@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Error;
+use std::path::Path;
+
+use prettytable::{color, Attr, Row};
+use serde_json::Value;
+
+use crate::engines::get_engines;
+use crate::node_module::{new_cell, PrintTable};
+use crate::pjson_detail::PjsonDetail;
+use crate::range::Range;
+use crate::semver::Semver;
+
+/// The tools a project can pin.
+pub const TOOLS: [&str; 4] = ["node", "npm", "yarn", "pnpm"];
+
+const VERSION_FILES: [&str; 2] = [".nvmrc", ".node-version"];
+
+/// A version of a tool the project asks for, and where it asks for it.
+#[derive(Debug, PartialEq)]
+pub struct Requirement {
+    /// Where the requirement was found, e.g. `.nvmrc` or `volta`.
+    pub source: String,
+    pub tool: String,
+    pub spec: String,
+}
+
+impl Requirement {
+    fn new(source: &str, tool: &str, spec: &str) -> Self {
+        Requirement {
+            source: source.to_string(),
+            tool: tool.to_string(),
+            spec: spec.trim().to_string(),
+        }
+    }
+
+    /// Returns the range of versions allowed, or None for aliases such as `lts/*` that depend on
+    /// what has been released.
+    pub fn range(&self) -> Option<Range> {
+        Range::parse(&self.spec)
+    }
+
+    /// Returns every requirement in the .nvmrc, .node-version and package.json of the project.
+    pub fn from(base_path: &Path, app_pjson: &PjsonDetail) -> Result<Vec<Self>, Error> {
+        let mut requirements = Vec::new();
+
+        for file_name in VERSION_FILES.iter() {
+            let path = base_path.join(file_name);
+            if !path.is_file() {
+                continue;
+            }
+            let contents = fs::read_to_string(path)?;
+            let spec = contents
+                .lines()
+                .map(|line| line.split('#').next().unwrap_or("").trim())
+                .find(|line| !line.is_empty());
+            if let Some(spec) = spec {
+                requirements.push(Requirement::new(file_name, "node", spec));
+            }
+        }
+
+        let engines = get_engines(app_pjson.engines.as_ref());
+        for tool in TOOLS.iter() {
+            if let Some(range) = engines.get(*tool) {
+                requirements.push(Requirement::new("engines", tool, range));
+            }
+        }
+
+        if let Some(Value::Object(volta)) = &app_pjson.volta {
+            for tool in TOOLS.iter() {
+                if let Some(Value::String(version)) = volta.get(*tool) {
+                    requirements.push(Requirement::new("volta", tool, version));
+                }
+            }
+        }
+
+        if let Some(package_manager) = &app_pjson.package_manager {
+            if let Some((tool, version)) = parse_package_manager(package_manager) {
+                requirements.push(Requirement::new("packageManager", tool, version));
+            }
+        }
+
+        Ok(requirements)
+    }
+}
+
+/// Returns the tool and version from a `packageManager` field such as `pnpm@8.15.4+sha256.abc`.
+pub fn parse_package_manager(package_manager: &str) -> Option<(&str, &str)> {
+    let (tool, version) = package_manager.split_at(package_manager.rfind('@')?);
+    let version = version[1..].split('+').next()?;
+    match tool.is_empty() || version.is_empty() {
+        true => None,
+        false => Some((tool, version)),
+    }
+}
+
+/// Two requirements for the same tool that no version can satisfy.
+pub struct Conflict<'a> {
+    pub first: &'a Requirement,
+    pub second: &'a Requirement,
+}
+
+/// Returns each pair of requirements for the same tool whose ranges do not overlap.
+pub fn get_conflicts(requirements: &[Requirement]) -> Vec<Conflict<'_>> {
+    let ranges: Vec<Option<Range>> = requirements.iter().map(Requirement::range).collect();
+    let mut conflicts = Vec::new();
+
+    for (i, first) in requirements.iter().enumerate() {
+        for (j, second) in requirements.iter().enumerate().skip(i + 1) {
+            if first.tool != second.tool {
+                continue;
+            }
+            if let (Some(a), Some(b)) = (&ranges[i], &ranges[j]) {
+                if !a.intersects(b) {
+                    conflicts.push(Conflict { first, second });
+                }
+            }
+        }
+    }
+    conflicts
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ToolchainStatus {
+    Satisfied,
+    Mismatch,
+    Missing,
+    /// The requirement is an alias or otherwise not a range that can be checked.
+    Unchecked,
+}
+
+/// A requirement compared with the version of the tool on PATH.
+pub struct ToolchainCheck<'a> {
+    pub requirement: &'a Requirement,
+    pub active: Option<&'a Semver>,
+    pub status: ToolchainStatus,
+}
+
+impl<'a> ToolchainCheck<'a> {
+    pub fn from(requirement: &'a Requirement, active: &'a HashMap<String, Option<Semver>>) -> Self {
+        let active = active.get(&requirement.tool).and_then(Option::as_ref);
+        let status = match (requirement.range(), active) {
+            (None, _) => ToolchainStatus::Unchecked,
+            (Some(_), None) => ToolchainStatus::Missing,
+            (Some(range), Some(version)) if range.satisfies(version) => ToolchainStatus::Satisfied,
+            (Some(_), Some(_)) => ToolchainStatus::Mismatch,
+        };
+        ToolchainCheck {
+            requirement,
+            active,
+            status,
+        }
+    }
+}
+
+impl<'a> PrintTable for ToolchainCheck<'a> {
+    fn table_row(&self) -> Row {
+        let (status, colour) = match self.status {
+            ToolchainStatus::Satisfied => ("ok", color::GREEN),
+            ToolchainStatus::Mismatch => ("mismatch", color::RED),
+            ToolchainStatus::Missing => ("not on PATH", color::YELLOW),
+            ToolchainStatus::Unchecked => ("unchecked", color::WHITE),
+        };
+        let active = match self.active {
+            Some(version) => format!("{} {}", self.requirement.tool, version),
+            None => String::new(),
+        };
+        Row::new(vec![
+            new_cell(&self.requirement.source),
+            new_cell(&format!("{} {}", self.requirement.tool, self.requirement.spec)),
+            new_cell(&active),
+            new_cell(status).with_style(Attr::ForegroundColor(colour)),
+        ])
+    }
+}
+
+impl<'a> PrintTable for Conflict<'a> {
+    fn table_row(&self) -> Row {
+        Row::new(vec![
+            new_cell(&self.first.tool),
+            new_cell(&format!("{} {}", self.first.source, self.first.spec)),
+            new_cell(&format!("{} {}", self.second.source, self.second.spec)),
+            new_cell("conflict").with_style(Attr::ForegroundColor(color::RED)),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_package_manager() {
+        assert_eq!(parse_package_manager("pnpm@8.15.4+sha256.abc"), Some(("pnpm", "8.15.4")));
+        assert_eq!(parse_package_manager("yarn@4.1.0"), Some(("yarn", "4.1.0")));
+        assert_eq!(parse_package_manager("yarn"), None);
+    }
+
+    #[test]
+    fn checks_requirements() {
+        let requirements = vec![
+            Requirement::new(".nvmrc", "node", "18"),
+            Requirement::new("engines", "node", ">=20"),
+            Requirement::new(".node-version", "node", "lts/*"),
+            Requirement::new("volta", "npm", "10.2.4"),
+            Requirement::new("packageManager", "pnpm", "8.15.4"),
+        ];
+
+        let conflicts = get_conflicts(&requirements);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!((conflicts[0].first.source.as_str(), conflicts[0].second.source.as_str()), (".nvmrc", "engines"));
+
+        let mut active = HashMap::new();
+        active.insert(String::from("node"), Semver::from(String::from("v16.20.2")));
+        active.insert(String::from("npm"), Semver::from(String::from("10.2.4")));
+        active.insert(String::from("pnpm"), None);
+        let statuses: Vec<ToolchainStatus> = requirements
+            .iter()
+            .map(|requirement| ToolchainCheck::from(requirement, &active).status)
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ToolchainStatus::Mismatch,
+                ToolchainStatus::Mismatch,
+                ToolchainStatus::Unchecked,
+                ToolchainStatus::Satisfied,
+                ToolchainStatus::Missing,
+            ]
+        );
+    }
+}
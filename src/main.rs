@@ -1,14 +1,69 @@
 use exitfailure::ExitFailure;
 
+use library::{Command, SnapshotCommand};
+
 fn main() -> Result<(), ExitFailure> {
     let cli = library::Cli::get();
 
-    if cli.global {
-        library::run_global()?;
-    } else if let Some(path) = &cli.diff {
-        library::run_diff(&path)?;
-    } else {
-        library::run_standard()?;
+    match &cli.command {
+        Some(Command::Outdated {
+            concurrency,
+            timeout,
+            offline,
+            cache,
+        }) => library::run_outdated(*concurrency, *timeout, *offline, cache.as_deref())?,
+        Some(Command::Freshness {
+            concurrency,
+            timeout,
+            packuments,
+        }) => library::run_freshness(*concurrency, *timeout, packuments.as_deref())?,
+        Some(Command::Licenses { policy }) => library::run_licenses(policy.as_deref())?,
+        Some(Command::Notices { output, markdown }) => library::run_notices(output.as_deref(), *markdown)?,
+        Some(Command::Advisories { database }) => library::run_advisories(database)?,
+        Some(Command::Size { packages, sort }) => library::run_size(*packages, sort)?,
+        Some(Command::Engines {
+            target_node,
+            target_npm,
+        }) => library::run_engines(target_node.as_deref(), target_npm.as_deref())?,
+        Some(Command::Toolchain) => library::run_toolchain()?,
+        Some(Command::Matrix {
+            projects,
+            inconsistent,
+        }) => library::run_matrix(projects, *inconsistent)?,
+        Some(Command::Scan {
+            root,
+            query,
+            format,
+            output,
+        }) => library::run_scan(root, query, format, output.as_deref())?,
+        Some(Command::ReleaseNotes {
+            from,
+            to,
+            production,
+            markdown,
+            output,
+        }) => library::run_release_notes(from, to.as_deref(), *production, *markdown, output.as_deref())?,
+        Some(Command::History { package }) => library::run_history(package)?,
+        Some(Command::Snapshot(SnapshotCommand::Save { file })) => library::run_snapshot_save(file)?,
+        Some(Command::Snapshot(SnapshotCommand::Diff { file })) => library::run_snapshot_diff(file)?,
+        Some(Command::Installations) => library::run_installations()?,
+        None => {
+            if cli.compare_global {
+                library::run_compare_global()?;
+            } else if cli.global && !cli.diff.is_empty() {
+                library::run_diff_global(&cli.diff)?;
+            } else if cli.global {
+                library::run_global()?;
+            } else if cli.registry_audit {
+                library::run_registry_audit()?;
+            } else if let Some(revisions) = &cli.diff_rev {
+                library::run_diff_rev(revisions)?;
+            } else if !cli.diff.is_empty() {
+                library::run_diff(&cli.diff)?;
+            } else {
+                library::run_standard()?;
+            }
+        }
     }
 
     Ok(())